@@ -1,22 +1,33 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::iter::FromIterator;
 
 use chrono::{DateTime, FixedOffset};
 
 use cli_table::Table;
 
+use crate::error::Error;
+use crate::geo::Coordinates;
+use crate::service::{ProviderRegistry, Slot};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Location {
-    longitude: f64,
-    latitude: f64,
+    #[serde(flatten)]
+    coordinates: Coordinates,
     city: Option<String>,
     cp: Option<String>,
 }
 
+impl Location {
+    pub fn coordinates(&self) -> Coordinates {
+        self.coordinates
+    }
+}
+
 type Date = Option<DateTime<FixedOffset>>;
 
 mod my_date_format {
-    use chrono::{DateTime, FixedOffset, NaiveDateTime};
+    use chrono::{DateTime, FixedOffset};
     use serde::{self, Deserialize, Deserializer, Serializer};
 
     pub fn serialize<S>(
@@ -38,16 +49,8 @@ mod my_date_format {
         D: Deserializer<'de>,
     {
         let s = Option::<String>::deserialize(deserializer)?;
-        s.map(|s| {
-            DateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S%.f%:z")
-                .or_else(|_| DateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S%.f%z"))
-                .or_else(|_| {
-                    NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S%.f")
-                        .map(|ndt| DateTime::from_utc(ndt, FixedOffset::east(2 * 3600)))
-                })
-                .map_err(serde::de::Error::custom)
-        })
-        .transpose()
+        s.map(|s| crate::util::parse_date(&s).map_err(serde::de::Error::custom))
+            .transpose()
     }
 }
 
@@ -97,27 +100,67 @@ pub struct Center {
     gid: String,
 }
 
-#[derive(Table, PartialOrd, PartialEq)]
+fn display_date(date: &Date) -> String {
+    date.map(|x| x.to_rfc2822()).unwrap_or_default()
+}
+
+fn display_pair(pair: &Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)>) -> String {
+    pair.map(|(first, second)| {
+        format!(
+            "{} / {}",
+            first.format("%d/%m %H:%M"),
+            second.format("%d/%m %H:%M")
+        )
+    })
+    .unwrap_or_default()
+}
+
+// serialized as a crate::format::Record
+#[derive(Debug, Clone, Table, PartialOrd, PartialEq)]
 pub struct CenterInfo {
     #[table(title = "Km")]
     pub distance: f64,
     #[table(title = "Slots")]
     pub n_slot: usize,
-    #[table(title = "Next RDV")]
-    date: String,
+    #[table(title = "Next RDV", display_fn = "display_date")]
+    pub next_slot: Date,
     #[table(title = "Address")]
-    address: String,
+    pub address: String,
     #[table(title = "URL")]
-    url: String,
+    pub url: String,
+    // earliest first dose with its second dose
+    #[table(title = "1st / 2nd dose", display_fn = "display_pair")]
+    pub pair: Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)>,
+    #[table(skip)]
+    pub name: String,
+    #[table(skip)]
+    pub vaccines: Vec<String>,
+    #[table(skip)]
+    pub slots: Vec<Slot>,
+    // requests sent to its provider, retries included
+    #[table(skip)]
+    pub attempts: u32,
 }
 
 impl Center {
-    pub fn has_chronodose(&self) -> bool {
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn plateforme(&self) -> Option<&str> {
+        self.plateforme.as_deref()
+    }
+
+    pub fn chronodose_count(&self) -> usize {
         self.appointment_schedules
             .as_ref()
             .and_then(|x| x.iter().find(|x| x.name == "chronodose"))
-            .map(|x| x.total > 0)
-            == Some(true)
+            .map(|x| x.total)
+            .unwrap_or_default()
+    }
+
+    pub fn has_chronodose(&self) -> bool {
+        self.chronodose_count() > 0
     }
 
     pub fn has_vaccine(&self, pat: &str) -> bool {
@@ -129,45 +172,63 @@ impl Center {
 
     pub async fn info(
         self,
-        latitude: f64,
-        longitude: f64,
+        origin: Coordinates,
         distance_limit: f64,
-    ) -> anyhow::Result<CenterInfo> {
+        providers: &ProviderRegistry,
+    ) -> crate::Result<CenterInfo> {
         let distance = self
             .location
             .as_ref()
-            .map(|location| {
-                crate::util::lat_long_to_km(
-                    latitude,
-                    longitude,
-                    location.latitude,
-                    location.longitude,
-                )
-            })
-            .unwrap_or(f64::MAX);
-
-        if distance <= distance_limit {
-            Ok(CenterInfo {
-                distance: (distance * 100.).round() / 100.,
-                n_slot: if self.url.contains("doctolib") {
-                    crate::service::doctolib::process_doctolib_center(&self.url, 0).await?
-                } else {
-                    self.appointment_schedules
-                        .as_ref()
-                        .and_then(|x| x.iter().find(|x| x.name == "chronodose"))
-                        .map(|x| x.total)
-                        .unwrap_or_default()
-                },
-                date: self
-                    .prochain_rdv
-                    .map(|x| x.to_rfc2822())
-                    .unwrap_or_default(),
-                address: self.metadata.address.to_owned(),
-                url: self.url.to_owned(),
-            })
-        } else {
-            Err(anyhow::Error::msg("distance filter"))
+            .map(|location| origin.distance_km(&location.coordinates))
+            .ok_or(Error::Unlocated)?;
+
+        if distance > distance_limit {
+            return Err(Error::OutOfRange {
+                distance,
+                limit: distance_limit,
+            });
+        }
+
+        let availability = providers.availability(&self).await?;
+        Ok(CenterInfo {
+            distance: (distance * 100.).round() / 100.,
+            n_slot: availability.n_slot(),
+            next_slot: availability
+                .next_slot()
+                .map(|x| x.start)
+                .or(self.prochain_rdv),
+            address: self.metadata.address.to_owned(),
+            url: self.url.to_owned(),
+            pair: availability
+                .earliest_pair()
+                .and_then(|x| x.second.map(|second| (x.first, second))),
+            name: self.nom.to_owned(),
+            vaccines: self.vaccine_type.clone().unwrap_or_default(),
+            slots: availability.slots,
+            attempts: availability.attempts,
+        })
+    }
+}
+
+// results of Center::info, filtered out centers apart from failed ones
+#[derive(Debug, Default)]
+pub struct CenterResults {
+    pub found: Vec<CenterInfo>,
+    pub filtered: Vec<Error>,
+    pub failed: Vec<Error>,
+}
+
+impl FromIterator<crate::Result<CenterInfo>> for CenterResults {
+    fn from_iter<I: IntoIterator<Item = crate::Result<CenterInfo>>>(iter: I) -> Self {
+        let mut results = CenterResults::default();
+        for result in iter {
+            match result {
+                Ok(info) => results.found.push(info),
+                Err(e) if e.is_filtered() => results.filtered.push(e),
+                Err(e) => results.failed.push(e),
+            }
         }
+        results
     }
 }
 
@@ -178,4 +239,7 @@ pub struct CentersInDepartment {
     last_scrap: Vec<String>,
     pub centres_disponibles: Vec<Center>,
     centres_indisponibles: Vec<Center>,
+    // requests needed to fetch the department, retries included
+    #[serde(skip)]
+    pub attempts: u32,
 }
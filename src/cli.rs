@@ -0,0 +1,350 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand};
+
+use covax::config::{Config, Profile};
+use covax::department::DepartmentCode;
+use covax::format::Format;
+use covax::http::{Http, HttpConfig};
+use covax::notify::webhook::WebhookConfig;
+use covax::service::doctolib::motive::{Dose, MotiveSelector, Vaccine};
+use covax::service::doctolib::{Doctolib, SecondDose};
+use covax::service::DateRange;
+use covax::vitemadose::GITLAB;
+
+// musee du Louvre
+static NEAR: &str = "48.864824,2.334595";
+static RADIUS_KM: f64 = 20.;
+static INTERVAL: Duration = Duration::from_secs(5 * 60);
+static JITTER: f64 = 0.2;
+
+// "20", "20km", "20 km" or "500m", in km
+fn parse_radius(s: &str) -> anyhow::Result<f64> {
+    let s = s.trim().to_ascii_lowercase();
+    let (value, unit) = match s.strip_suffix("km") {
+        Some(value) => (value, 1.),
+        None => match s.strip_suffix('m') {
+            Some(value) => (value, 0.001),
+            None => (s.as_str(), 1.),
+        },
+    };
+    let radius: f64 = value.trim().parse()?;
+    if radius < 0. || !radius.is_finite() {
+        return Err(anyhow::anyhow!("invalid radius: {}", s));
+    }
+    Ok(radius * unit)
+}
+
+fn parse_jitter(s: &str) -> anyhow::Result<f64> {
+    covax::util::fraction(s.trim().parse()?)
+}
+
+// flags given on the command line take precedence over the profile
+fn or_profile<T: Clone>(flags: &[T], profile: &[T]) -> Vec<T> {
+    if flags.is_empty() { profile } else { flags }.to_vec()
+}
+
+#[derive(Debug, Parser)]
+#[clap(version, about = "Looks for covid-19 vaccination slots in France")]
+pub struct Cli {
+    /// Configuration file, ~/.config/covax/config.toml by default
+    #[clap(long, global = true)]
+    pub config: Option<PathBuf>,
+    /// Profile of the configuration file to use
+    #[clap(long, short, global = true)]
+    pub profile: Option<String>,
+    /// ViteMaDose data, mirrors serve the same files
+    #[clap(long, global = true)]
+    pub base_url: Option<String>,
+    /// Communes JSON searched instead of ViteMaDose, as served by it or a list of communes
+    #[clap(long, global = true)]
+    pub communes_file: Option<PathBuf>,
+    /// Request timeout in seconds
+    #[clap(long, global = true)]
+    pub timeout: Option<u64>,
+    /// Requests in flight at once
+    #[clap(long, global = true)]
+    pub concurrency: Option<usize>,
+    #[clap(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Centers with slots around a place
+    Search(Search),
+    /// Repeats a search and prints the centers and slots which appeared or disappeared
+    Watch(Watch),
+    /// Slots of a single Doctolib center
+    Center(CenterArgs),
+    /// Departments with their region
+    Departments,
+    /// Communes matching a name, postcode or INSEE code
+    Communes { query: String },
+}
+
+// where and what to look for, shared by search and watch
+#[derive(Debug, Args)]
+pub struct Query {
+    /// Commune name, postcode, INSEE code or "lat,long", musee du Louvre by default
+    #[clap(long)]
+    pub near: Option<String>,
+    /// Distance from --near, such as 20km or 500m, 20km by default
+    #[clap(long, parse(try_from_str = parse_radius))]
+    pub radius: Option<f64>,
+    /// Department to look up instead of those around --near, may be repeated
+    #[clap(long)]
+    pub department: Vec<DepartmentCode>,
+    /// Only centers with chronodoses
+    #[clap(long, overrides_with = "no_chronodose")]
+    pub chronodose: bool,
+    /// Also centers without chronodoses, when the profile asks for them
+    #[clap(long, overrides_with = "chronodose")]
+    pub no_chronodose: bool,
+    #[clap(flatten)]
+    pub slots: SlotArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct Search {
+    #[clap(flatten)]
+    pub query: Query,
+    /// Also list centers without slots
+    #[clap(long)]
+    pub all: bool,
+    /// Output format: table, json, ndjson, csv or markdown
+    #[clap(long)]
+    pub format: Option<Format>,
+    /// Print the full scan report as json on stderr
+    #[clap(long)]
+    pub report_json: bool,
+    /// Send the centers with slots to the notifiers of the configuration
+    #[clap(long)]
+    pub notify: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct Watch {
+    #[clap(flatten)]
+    pub query: Query,
+    /// Time between searches, such as 90s or 5m, 5m by default
+    #[clap(long, parse(try_from_str = humantime::parse_duration))]
+    pub interval: Option<Duration>,
+    /// Fraction of the interval randomly added or removed, 0.2 by default
+    #[clap(long, parse(try_from_str = parse_jitter))]
+    pub jitter: Option<f64>,
+    /// Print changes as json lines
+    #[clap(long)]
+    pub json: bool,
+    /// Webhook to post new slots to, as <url> for json or <kind>=<url> with kind
+    /// json, slack or discord, may be repeated; matrix is only set in the configuration file
+    #[clap(long)]
+    pub webhook: Vec<WebhookConfig>,
+    /// Show desktop notifications, opening the center page on click
+    #[clap(long)]
+    pub desktop: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct CenterArgs {
+    /// Doctolib booking page of the center
+    pub url: String,
+    #[clap(flatten)]
+    pub slots: SlotArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct SlotArgs {
+    /// Vaccine to look for, may be repeated, any by default
+    #[clap(long)]
+    pub vaccine: Vec<Vaccine>,
+    /// Dose to look for: 1, 2 or booster, 1 by default
+    #[clap(long)]
+    pub dose: Vec<Dose>,
+    /// Age of the person to vaccinate
+    #[clap(long)]
+    pub age: Option<u8>,
+    /// First day to look for slots, today by default
+    #[clap(long)]
+    pub from: Option<NaiveDate>,
+    /// Days to look for slots, 2 by default
+    #[clap(long)]
+    pub days: Option<usize>,
+    /// Second dose: ignore, pair (earliest one per first dose slot) or require (pair and drop
+    /// unpaired slots), ignore by default
+    #[clap(long)]
+    pub second_dose: Option<SecondDose>,
+    /// Days after the first dose to look for the second one from, 21 by default
+    #[clap(long)]
+    pub second_dose_delay: Option<usize>,
+}
+
+impl Cli {
+    pub fn load_config(&self) -> anyhow::Result<Config> {
+        match &self.config {
+            Some(path) => Config::load(path),
+            None => Config::load_default(),
+        }
+    }
+
+    pub fn base_url(&self, config: &Config) -> String {
+        self.base_url
+            .clone()
+            .or_else(|| config.base_url.clone())
+            .unwrap_or_else(|| GITLAB.to_owned())
+    }
+
+    pub fn communes_file(&self, config: &Config) -> Option<PathBuf> {
+        self.communes_file
+            .clone()
+            .or_else(|| config.communes_file.clone())
+    }
+
+    pub fn http_config(&self, config: &Config) -> HttpConfig {
+        let mut http = config.http.clone();
+        if let Some(timeout) = self.timeout {
+            http.timeout = Some(Duration::from_secs(timeout));
+        }
+        if let Some(concurrency) = self.concurrency {
+            http.scheduler.concurrency = concurrency;
+        }
+        http
+    }
+}
+
+impl Query {
+    pub fn near(&self, profile: &Profile) -> String {
+        self.near
+            .clone()
+            .or_else(|| profile.near.clone())
+            .unwrap_or_else(|| NEAR.to_owned())
+    }
+
+    pub fn radius_km(&self, profile: &Profile) -> f64 {
+        self.radius.or(profile.radius_km).unwrap_or(RADIUS_KM)
+    }
+
+    pub fn departments(&self, profile: &Profile) -> Vec<DepartmentCode> {
+        or_profile(&self.department, &profile.departments)
+    }
+
+    pub fn chronodose(&self, profile: &Profile) -> bool {
+        // the last of --chronodose and --no-chronodose wins
+        (self.chronodose || self.no_chronodose)
+            .then_some(self.chronodose)
+            .or(profile.chronodose)
+            .unwrap_or(false)
+    }
+}
+
+impl Search {
+    pub fn format(&self, profile: &Profile) -> Format {
+        self.format.or(profile.format).unwrap_or_default()
+    }
+}
+
+impl Watch {
+    pub fn interval(&self, profile: &Profile) -> Duration {
+        self.interval.or(profile.interval).unwrap_or(INTERVAL)
+    }
+
+    pub fn jitter(&self, profile: &Profile) -> f64 {
+        self.jitter.or(profile.jitter).unwrap_or(JITTER)
+    }
+}
+
+impl SlotArgs {
+    pub fn vaccines(&self, profile: &Profile) -> Vec<Vaccine> {
+        or_profile(&self.vaccine, &profile.vaccines)
+    }
+
+    pub fn doctolib(&self, profile: &Profile, http: &Http) -> Doctolib {
+        let default = Doctolib::default();
+
+        let mut motives = profile
+            .motives
+            .clone()
+            .unwrap_or_else(|| MotiveSelector::default().dose(Dose::First));
+        motives.vaccines = self.vaccines(profile);
+        motives.doses = or_profile(&self.dose, &motives.doses);
+        motives.age = self.age.or(motives.age);
+
+        Doctolib {
+            range: DateRange {
+                start: self.from.or(profile.from),
+                days: self.days.or(profile.days).unwrap_or(default.range.days),
+            },
+            motives,
+            second_dose: self
+                .second_dose
+                .or(profile.second_dose)
+                .unwrap_or(default.second_dose),
+            second_dose_delay: self
+                .second_dose_delay
+                .or(profile.second_dose_delay)
+                .unwrap_or(default.second_dose_delay),
+            http: http.clone(),
+            ..default
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(args: &[&str]) -> Query {
+        let cli = Cli::try_parse_from([&["covax", "search"], args].concat()).unwrap();
+        match cli.command {
+            Command::Search(search) => search.query,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn chronodose_flags_override_the_profile() {
+        let chronodose = Profile {
+            chronodose: Some(true),
+            ..Default::default()
+        };
+        let default = Profile::default();
+
+        assert!(query(&[]).chronodose(&chronodose));
+        assert!(!query(&["--no-chronodose"]).chronodose(&chronodose));
+        assert!(query(&["--chronodose"]).chronodose(&default));
+        assert!(!query(&[]).chronodose(&default));
+        assert!(query(&["--no-chronodose", "--chronodose"]).chronodose(&default));
+        assert!(!query(&["--chronodose", "--no-chronodose"]).chronodose(&chronodose));
+    }
+
+    #[test]
+    fn jitter_is_a_fraction() {
+        let watch = |jitter: &str| Cli::try_parse_from(["covax", "watch", "--jitter", jitter]);
+        assert!(watch("0.5").is_ok());
+        assert!(watch("1").is_ok());
+        for jitter in &["NaN", "inf", "-0.1", "1.5", "x"] {
+            assert!(watch(jitter).is_err(), "{}", jitter);
+        }
+    }
+
+    #[test]
+    fn second_dose_flags_override_the_profile() {
+        let profile = Profile {
+            second_dose: Some(SecondDose::Pair),
+            second_dose_delay: Some(28),
+            ..Default::default()
+        };
+        let http = Http::default();
+
+        let doctolib = query(&[]).slots.doctolib(&profile, &http);
+        assert_eq!(doctolib.second_dose, SecondDose::Pair);
+        assert_eq!(doctolib.second_dose_delay, 28);
+
+        let args = ["--second-dose", "require", "--second-dose-delay", "35"];
+        let doctolib = query(&args).slots.doctolib(&profile, &http);
+        assert_eq!(doctolib.second_dose, SecondDose::Require);
+        assert_eq!(doctolib.second_dose_delay, 35);
+    }
+}
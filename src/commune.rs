@@ -1,50 +1,41 @@
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
 
+use crate::department::DepartmentCode;
+use crate::geo::Coordinates;
+use crate::util::normalize_name;
+
+lazy_static::lazy_static! {
+    static ref BUNDLED: CommuneIndex = CommuneIndex::from_json(include_str!("data/communes.json")).unwrap();
+}
+
 mod my_geolocation_format {
-    use super::GeoLocation;
+    use crate::geo::Coordinates;
     use serde::{self, Deserialize, Deserializer, Serializer};
 
-    pub fn serialize<S>(geolocation: &Option<GeoLocation>, serializer: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S>(geolocation: &Option<Coordinates>, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
         if let Some(geolocation) = geolocation {
-            serializer.serialize_bytes(
-                format!("{},{}", geolocation.latitude, geolocation.longitude).as_bytes(),
-            )
+            serializer.serialize_str(&geolocation.to_string())
         } else {
             serializer.serialize_none()
         }
     }
 
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<GeoLocation>, D::Error>
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Coordinates>, D::Error>
     where
         D: Deserializer<'de>,
     {
         let s = Option::<String>::deserialize(deserializer)?;
-        s.map(|s| {
-            if let Some((lat, long)) = s.split_once(',') {
-                let latitude = lat.parse().map_err(serde::de::Error::custom)?;
-                let longitude = long.parse().map_err(serde::de::Error::custom)?;
-                Ok(GeoLocation {
-                    longitude,
-                    latitude,
-                })
-            } else {
-                Err(serde::de::Error::custom("couldn't split"))
-            }
-        })
-        .transpose()
+        s.map(|s| s.parse().map_err(serde::de::Error::custom))
+            .transpose()
     }
 }
 
-#[derive(Debug)]
-pub struct GeoLocation {
-    longitude: f64,
-    latitude: f64,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Commune {
     c: String,
     z: String,
@@ -52,7 +43,7 @@ pub struct Commune {
     d: Option<String>,
     #[serde(default)]
     #[serde(with = "my_geolocation_format")]
-    g: Option<GeoLocation>,
+    g: Option<Coordinates>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -60,3 +51,136 @@ pub struct CommuneResponse {
     query: String,
     communes: Vec<Commune>,
 }
+
+impl Commune {
+    pub fn insee(&self) -> &str {
+        &self.c
+    }
+
+    pub fn postcode(&self) -> &str {
+        &self.z
+    }
+
+    pub fn name(&self) -> &str {
+        &self.n
+    }
+
+    pub fn location(&self) -> Option<Coordinates> {
+        self.g
+    }
+
+    pub fn department(&self) -> Option<DepartmentCode> {
+        self.d.as_deref().and_then(|d| d.parse().ok()).or_else(|| {
+            // first two digits of the insee code, three for outre-mer
+            let len = if self.c.starts_with("97") { 3 } else { 2 };
+            self.c.get(..len).and_then(|d| d.parse().ok())
+        })
+    }
+}
+
+impl CommuneResponse {
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn communes(&self) -> &[Commune] {
+        &self.communes
+    }
+
+    pub fn into_communes(self) -> Vec<Commune> {
+        self.communes
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CommuneIndex {
+    communes: Vec<Commune>,
+}
+
+impl From<Vec<Commune>> for CommuneIndex {
+    fn from(communes: Vec<Commune>) -> Self {
+        Self { communes }
+    }
+}
+
+impl From<CommuneResponse> for CommuneIndex {
+    fn from(response: CommuneResponse) -> Self {
+        response.into_communes().into()
+    }
+}
+
+impl CommuneIndex {
+    // accepts either a ViteMaDose `CommuneResponse` or a plain list of communes
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Communes {
+            Response(CommuneResponse),
+            List(Vec<Commune>),
+        }
+
+        Ok(match serde_json::from_str(json)? {
+            Communes::Response(response) => response.into(),
+            Communes::List(communes) => communes.into(),
+        })
+    }
+
+    // prefectures and a few large cities, shipped with the crate for offline lookups
+    pub fn bundled() -> &'static CommuneIndex {
+        &BUNDLED
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Self::from_json(&json))
+            .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))
+    }
+
+    pub fn communes(&self) -> &[Commune] {
+        &self.communes
+    }
+
+    pub fn by_postcode(&self, postcode: &str) -> Vec<&Commune> {
+        self.communes.iter().filter(|c| c.z == postcode).collect()
+    }
+
+    pub fn by_insee(&self, insee: &str) -> Option<&Commune> {
+        self.communes
+            .iter()
+            .find(|c| c.c.eq_ignore_ascii_case(insee))
+    }
+
+    // exact (accent-insensitive) matches first, then prefix matches
+    pub fn by_name(&self, name: &str) -> Vec<&Commune> {
+        let name = normalize_name(name);
+        let (mut exact, prefix): (Vec<_>, Vec<_>) = self
+            .communes
+            .iter()
+            .map(|c| (c, normalize_name(&c.n)))
+            .filter(|(_, n)| n.starts_with(&name))
+            .partition(|(_, n)| n == &name);
+        exact.extend(prefix);
+        exact.into_iter().map(|(c, _)| c).collect()
+    }
+
+    // a five character query is tried as a postcode, then as an insee code
+    pub fn search(&self, query: &str) -> Vec<&Commune> {
+        let query = query.trim();
+        if query.len() == 5 && query.chars().all(|c| c.is_ascii_alphanumeric()) {
+            let found = self.by_postcode(query);
+            if !found.is_empty() {
+                return found;
+            }
+            if let Some(commune) = self.by_insee(query) {
+                return vec![commune];
+            }
+        }
+        self.by_name(query)
+    }
+
+    pub fn locate(&self, query: &str) -> Option<&Commune> {
+        self.search(query).into_iter().find(|c| c.g.is_some())
+    }
+}
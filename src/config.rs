@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::department::DepartmentCode;
+use crate::format::Format;
+use crate::http::HttpConfig;
+use crate::notify::email::SmtpConfig;
+use crate::notify::webhook::WebhookConfig;
+use crate::service::doctolib::motive::{MotiveSelector, Vaccine};
+use crate::service::doctolib::SecondDose;
+
+// a saved search, unset fields fall back to the command-line defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    // commune name, postcode, INSEE code or "lat,long"
+    pub near: Option<String>,
+    pub radius_km: Option<f64>,
+    // looked up instead of the departments around `near`
+    pub departments: Vec<DepartmentCode>,
+    pub vaccines: Vec<Vaccine>,
+    pub chronodose: Option<bool>,
+    // doses, age and keywords of the doctolib visit motives, vaccines are taken from above
+    pub motives: Option<MotiveSelector>,
+    pub format: Option<Format>,
+    // doctolib settings
+    pub from: Option<NaiveDate>,
+    pub days: Option<usize>,
+    pub second_dose: Option<SecondDose>,
+    pub second_dose_delay: Option<usize>,
+    // covax watch
+    #[serde(with = "humantime_serde")]
+    pub interval: Option<Duration>,
+    #[serde(deserialize_with = "crate::util::deserialize_optional_fraction")]
+    pub jitter: Option<f64>,
+}
+
+// notifications sent by covax watch
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifyConfig {
+    pub webhooks: Vec<WebhookConfig>,
+    pub email: Option<SmtpConfig>,
+    // needs the desktop feature
+    pub desktop: bool,
+}
+
+// ~/.config/covax/config.toml
+//
+//     [http]
+//     timeout = "20s"
+//
+//     [profiles.paris]
+//     near = "75001"
+//     radius_km = 20
+//     vaccines = ["pfizer"]
+//
+//     [[notify.webhooks]]
+//     kind = "slack"
+//     url = "https://hooks.slack.com/services/..."
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub base_url: Option<String>,
+    // communes looked up locally instead of on ViteMaDose
+    pub communes_file: Option<PathBuf>,
+    // used when no profile is given
+    pub default_profile: Option<String>,
+    pub http: HttpConfig,
+    pub notify: NotifyConfig,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|x| x.join("covax").join("config.toml"))
+    }
+
+    pub fn from_toml(toml: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let toml = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+        Self::from_toml(&toml).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))
+    }
+
+    // the default file if it exists, an empty config otherwise
+    pub fn load_default() -> anyhow::Result<Self> {
+        match Self::default_path().filter(|x| x.exists()) {
+            Some(path) => Self::load(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    // the named profile, or the default one, or an empty one
+    pub fn profile(&self, name: Option<&str>) -> anyhow::Result<Profile> {
+        match name.or(self.default_profile.as_deref()) {
+            Some(name) => self.profiles.get(name).cloned().ok_or_else(|| {
+                let names: Vec<_> = self.profiles.keys().map(|x| x.as_str()).collect();
+                anyhow::anyhow!(
+                    "unknown profile: {}, expected one of [{}]",
+                    name,
+                    names.join(", ")
+                )
+            }),
+            None => Ok(Profile::default()),
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::geo::{BoundingBox, Coordinates};
+use crate::util::normalize_name;
+
+lazy_static::lazy_static! {
+    static ref DEPTS: Vec<Department> = serde_json::from_str(include_str!("data/departements.json")).unwrap();
+}
+
+// a code listed in the departments registry, or "om"
+// only built by parsing, so that it always names a department ViteMaDose serves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DepartmentCode(Code);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Code {
+    // 01 to 95, except 20
+    Metropole(u8),
+    // 2A
+    CorseDuSud,
+    // 2B
+    HauteCorse,
+    // 971 to 976
+    OutreMer(u16),
+    // collectivites d'outre-mer, grouped by ViteMaDose
+    AutresOutreMer,
+}
+
+impl DepartmentCode {
+    pub fn all() -> Vec<Self> {
+        // the registry, then "om" which ViteMaDose lists apart
+        let mut codes: Vec<_> = DEPTS.iter().map(Department::code).collect();
+        codes.push(DepartmentCode(Code::AutresOutreMer));
+        codes
+    }
+
+    pub fn is_metropole(&self) -> bool {
+        matches!(
+            self.0,
+            Code::Metropole(_) | Code::CorseDuSud | Code::HauteCorse
+        )
+    }
+
+    pub fn is_outre_mer(&self) -> bool {
+        !self.is_metropole()
+    }
+
+    // well-formed, whether or not the registry has it, which is read with it
+    fn parse_unchecked(s: &str) -> anyhow::Result<Self> {
+        let invalid = || anyhow::anyhow!("invalid department code: {}", s);
+        let code = match s.trim().to_ascii_uppercase().as_str() {
+            "2A" => Code::CorseDuSud,
+            "2B" => Code::HauteCorse,
+            "OM" => Code::AutresOutreMer,
+            code => match code.parse::<u16>().map_err(|_| invalid())? {
+                code @ 1..=95 => Code::Metropole(code as u8),
+                code @ 971..=976 => Code::OutreMer(code),
+                _ => return Err(invalid()),
+            },
+        };
+        Ok(DepartmentCode(code))
+    }
+
+    fn deserialize_unchecked<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::parse_unchecked(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for DepartmentCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Code::Metropole(d) => write!(f, "{:02}", d),
+            Code::CorseDuSud => write!(f, "2A"),
+            Code::HauteCorse => write!(f, "2B"),
+            Code::OutreMer(d) => write!(f, "{}", d),
+            Code::AutresOutreMer => write!(f, "om"),
+        }
+    }
+}
+
+impl TryFrom<u16> for DepartmentCode {
+    type Error = anyhow::Error;
+
+    fn try_from(code: u16) -> anyhow::Result<Self> {
+        format!("{:02}", code).parse()
+    }
+}
+
+impl FromStr for DepartmentCode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let code = Self::parse_unchecked(s)?;
+        if code.0 == Code::AutresOutreMer || Department::by_code(code).is_some() {
+            Ok(code)
+        } else {
+            Err(anyhow::anyhow!("unknown department code: {}", s))
+        }
+    }
+}
+
+impl TryFrom<String> for DepartmentCode {
+    type Error = anyhow::Error;
+
+    fn try_from(code: String) -> anyhow::Result<Self> {
+        code.parse()
+    }
+}
+
+impl From<DepartmentCode> for String {
+    fn from(code: DepartmentCode) -> Self {
+        code.to_string()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Department {
+    #[serde(deserialize_with = "DepartmentCode::deserialize_unchecked")]
+    code_departement: DepartmentCode,
+    nom_departement: String,
+    code_region: f64,
+    nom_region: String,
+    // prefecture location
+    #[serde(flatten)]
+    centre: Coordinates,
+    // smallest box containing the department, islands included
+    bbox: BoundingBox,
+}
+
+impl Department {
+    pub fn all() -> &'static [Department] {
+        &DEPTS
+    }
+
+    pub fn metropole() -> Vec<&'static Department> {
+        DEPTS.iter().filter(|d| d.code().is_metropole()).collect()
+    }
+
+    pub fn outre_mer() -> Vec<&'static Department> {
+        DEPTS.iter().filter(|d| d.code().is_outre_mer()).collect()
+    }
+
+    pub fn by_code(code: DepartmentCode) -> Option<&'static Department> {
+        DEPTS.iter().find(|d| d.code_departement == code)
+    }
+
+    pub fn by_name(name: &str) -> Option<&'static Department> {
+        let name = normalize_name(name);
+        DEPTS
+            .iter()
+            .find(|d| normalize_name(&d.nom_departement) == name)
+    }
+
+    pub fn by_region(region: &str) -> Vec<&'static Department> {
+        let region = normalize_name(region);
+        DEPTS
+            .iter()
+            .filter(|d| normalize_name(&d.nom_region) == region)
+            .collect()
+    }
+
+    pub fn by_region_code(region_code: u8) -> Vec<&'static Department> {
+        DEPTS
+            .iter()
+            .filter(|d| d.region_code() == region_code)
+            .collect()
+    }
+
+    // departments whose bounding box meets the circle of radius_km around origin,
+    // nearest first, those containing origin by distance to their prefecture
+    pub fn around(origin: &Coordinates, radius_km: f64) -> Vec<&'static Department> {
+        let mut depts: Vec<_> = DEPTS
+            .iter()
+            .map(|d| {
+                let border = origin.distance_km(&d.bbox.nearest(origin));
+                (d, border, d.centre.distance_km(origin))
+            })
+            .filter(|(_, border, _)| *border <= radius_km)
+            .collect();
+        depts.sort_by(|a, b| {
+            (a.1, a.2)
+                .partial_cmp(&(b.1, b.2))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        depts.into_iter().map(|(d, _, _)| d).collect()
+    }
+
+    pub fn code(&self) -> DepartmentCode {
+        self.code_departement
+    }
+
+    pub fn name(&self) -> &str {
+        &self.nom_departement
+    }
+
+    pub fn region_code(&self) -> u8 {
+        self.code_region as u8
+    }
+
+    pub fn region_name(&self) -> &str {
+        &self.nom_region
+    }
+
+    pub fn centre(&self) -> Coordinates {
+        self.centre
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        self.bbox
+    }
+}
@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    // filters, the center was not looked at
+    #[error("{distance:.2} km away, farther than {limit} km")]
+    OutOfRange { distance: f64, limit: f64 },
+    #[error("no location")]
+    Unlocated,
+
+    // failures
+    #[error("no provider for {0}")]
+    NoProvider(String),
+    #[error("rate limited by {host}")]
+    RateLimited {
+        host: String,
+        retry_after: Option<Duration>,
+    },
+    #[error("network error: {0:#}")]
+    Network(anyhow::Error),
+    #[error("parse error: {0:#}")]
+    Parse(anyhow::Error),
+    #[error("{provider}: {source:#}")]
+    Provider {
+        provider: String,
+        source: anyhow::Error,
+    },
+}
+
+impl Error {
+    // the center was left out on purpose, not because of a failure
+    pub fn is_filtered(&self) -> bool {
+        matches!(self, Error::OutOfRange { .. } | Error::Unlocated)
+    }
+
+    // short name of the variant, to group errors by kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::OutOfRange { .. } => "out of range",
+            Error::Unlocated => "unlocated",
+            Error::NoProvider(_) => "no provider",
+            Error::RateLimited { .. } => "rate limited",
+            Error::Network(_) => "network",
+            Error::Parse(_) => "parse",
+            Error::Provider { .. } => "provider",
+        }
+    }
+
+    // classifies an error returned by a provider from its cause
+    pub fn provider(provider: &str, error: anyhow::Error) -> Self {
+        let error = match error.downcast::<Error>() {
+            Ok(error) => return error,
+            Err(error) => error,
+        };
+        if let Some(e) = error
+            .chain()
+            .find_map(|x| x.downcast_ref::<reqwest::Error>())
+        {
+            if e.is_decode() {
+                Error::Parse(error)
+            } else {
+                Error::Network(error)
+            }
+        } else if error.chain().any(|x| x.is::<serde_json::Error>()) {
+            Error::Parse(error)
+        } else {
+            Error::Provider {
+                provider: provider.to_owned(),
+                source: error,
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset};
+use cli_table::WithTitle;
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::center::CenterInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Table,
+    Json,
+    // one json record per line
+    Ndjson,
+    Csv,
+    Markdown,
+}
+
+impl Format {
+    pub fn all() -> [Format; 5] {
+        [
+            Format::Table,
+            Format::Json,
+            Format::Ndjson,
+            Format::Csv,
+            Format::Markdown,
+        ]
+    }
+
+    pub fn write<W: Write>(&self, writer: W, centers: &[CenterInfo]) -> anyhow::Result<()> {
+        match self {
+            Format::Table => write_table(writer, centers),
+            Format::Json => write_json(writer, centers),
+            Format::Ndjson => write_ndjson(writer, centers),
+            Format::Csv => write_csv(writer, centers),
+            Format::Markdown => write_markdown(writer, centers),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Table => write!(f, "table"),
+            Format::Json => write!(f, "json"),
+            Format::Ndjson => write!(f, "ndjson"),
+            Format::Csv => write!(f, "csv"),
+            Format::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        Format::all()
+            .iter()
+            .copied()
+            .find(|x| x.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| anyhow::anyhow!("unknown format: {}", s))
+    }
+}
+
+// the schema shared by every machine-readable format, fields are only ever added at the end
+// dates are RFC 3339, absent values are null in json and empty in csv and markdown,
+// where lists are joined with "; " and slots written as "<start> (<motive>)"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub name: String,
+    pub address: String,
+    pub url: String,
+    pub distance_km: f64,
+    pub slot_count: usize,
+    pub next_slot: Option<DateTime<FixedOffset>>,
+    pub first_dose: Option<DateTime<FixedOffset>>,
+    pub second_dose: Option<DateTime<FixedOffset>>,
+    pub vaccines: Vec<String>,
+    pub slots: Vec<RecordSlot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordSlot {
+    pub start: DateTime<FixedOffset>,
+    pub motive: Option<String>,
+}
+
+impl fmt::Display for RecordSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.start.to_rfc3339())?;
+        if let Some(motive) = &self.motive {
+            write!(f, " ({})", motive)?;
+        }
+        Ok(())
+    }
+}
+
+impl Record {
+    pub const FIELDS: [&'static str; 10] = [
+        "name",
+        "address",
+        "url",
+        "distance_km",
+        "slot_count",
+        "next_slot",
+        "first_dose",
+        "second_dose",
+        "vaccines",
+        "slots",
+    ];
+
+    fn values(&self) -> [String; 10] {
+        let date =
+            |x: &Option<DateTime<FixedOffset>>| x.map(|x| x.to_rfc3339()).unwrap_or_default();
+        [
+            self.name.clone(),
+            self.address.clone(),
+            self.url.clone(),
+            self.distance_km.to_string(),
+            self.slot_count.to_string(),
+            date(&self.next_slot),
+            date(&self.first_dose),
+            date(&self.second_dose),
+            self.vaccines.join("; "),
+            self.slots
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+                .join("; "),
+        ]
+    }
+}
+
+impl From<&CenterInfo> for Record {
+    fn from(center: &CenterInfo) -> Self {
+        Self {
+            name: center.name.clone(),
+            address: center.address.clone(),
+            url: center.url.clone(),
+            distance_km: center.distance,
+            slot_count: center.n_slot,
+            next_slot: center.next_slot,
+            first_dose: center.pair.map(|x| x.0),
+            second_dose: center.pair.map(|x| x.1),
+            vaccines: center.vaccines.clone(),
+            slots: center
+                .slots
+                .iter()
+                .map(|x| RecordSlot {
+                    start: x.start,
+                    motive: x.motive.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Serialize for CenterInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Record::from(self).serialize(serializer)
+    }
+}
+
+pub fn write_table<W: Write>(mut writer: W, centers: &[CenterInfo]) -> anyhow::Result<()> {
+    writeln!(writer, "{}", centers.with_title().display()?)?;
+    Ok(())
+}
+
+pub fn write_json<W: Write>(mut writer: W, centers: &[CenterInfo]) -> anyhow::Result<()> {
+    serde_json::to_writer_pretty(&mut writer, centers)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+pub fn write_ndjson<W: Write>(mut writer: W, centers: &[CenterInfo]) -> anyhow::Result<()> {
+    for center in centers {
+        serde_json::to_writer(&mut writer, center)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+// with a header, even without centers
+pub fn write_csv<W: Write>(writer: W, centers: &[CenterInfo]) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(Record::FIELDS)?;
+    for center in centers {
+        writer.write_record(Record::from(center).values())?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn write_markdown<W: Write>(mut writer: W, centers: &[CenterInfo]) -> anyhow::Result<()> {
+    let escape = |x: &str| x.replace('|', "\\|").replace('\n', " ");
+    writeln!(writer, "| {} |", Record::FIELDS.join(" | "))?;
+    writeln!(writer, "|{}", " --- |".repeat(Record::FIELDS.len()))?;
+    for center in centers {
+        let values: Vec<_> = Record::from(center)
+            .values()
+            .iter()
+            .map(|x| escape(x))
+            .collect();
+        writeln!(writer, "| {} |", values.join(" | "))?;
+    }
+    Ok(())
+}
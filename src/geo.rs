@@ -0,0 +1,143 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+pub const EARTH_RADIUS_KM: f64 = 6371.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Coordinates {
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self {
+            latitude,
+            longitude,
+        }
+    }
+
+    // great-circle distance, haversine formula
+    pub fn distance_km(&self, other: &Coordinates) -> f64 {
+        let lat1 = self.latitude.to_radians();
+        let lat2 = other.latitude.to_radians();
+        let d_lat = lat2 - lat1;
+        let d_long = (other.longitude - self.longitude).to_radians();
+
+        let a = (d_lat / 2.).sin().powi(2) + lat1.cos() * lat2.cos() * (d_long / 2.).sin().powi(2);
+
+        EARTH_RADIUS_KM * 2. * a.sqrt().min(1.).asin()
+    }
+
+    // initial bearing in degrees, clockwise from north in [0, 360)
+    pub fn bearing(&self, other: &Coordinates) -> f64 {
+        let lat1 = self.latitude.to_radians();
+        let lat2 = other.latitude.to_radians();
+        let d_long = (other.longitude - self.longitude).to_radians();
+
+        let y = d_long.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * d_long.cos();
+
+        (y.atan2(x).to_degrees() + 360.) % 360.
+    }
+
+    // smallest box containing the circle of radius_km around self
+    // does not wrap around the antimeridian
+    pub fn bounding_box(&self, radius_km: f64) -> BoundingBox {
+        let d_lat = (radius_km / EARTH_RADIUS_KM).to_degrees();
+        let d_long = (radius_km / (EARTH_RADIUS_KM * self.latitude.to_radians().cos()))
+            .to_degrees()
+            .min(180.);
+
+        BoundingBox {
+            min: Coordinates::new(
+                (self.latitude - d_lat).max(-90.),
+                (self.longitude - d_long).max(-180.),
+            ),
+            max: Coordinates::new(
+                (self.latitude + d_lat).min(90.),
+                (self.longitude + d_long).min(180.),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Coordinates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.latitude, self.longitude)
+    }
+}
+
+// "lat,long"
+impl FromStr for Coordinates {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (lat, long) = s
+            .split_once(',')
+            .ok_or_else(|| anyhow::anyhow!("couldn't split: {}", s))?;
+        let latitude: f64 = lat.trim().parse()?;
+        let longitude: f64 = long.trim().parse()?;
+        if !(-90. ..=90.).contains(&latitude) || !(-180. ..=180.).contains(&longitude) {
+            return Err(anyhow::anyhow!("out of range coordinates: {}", s));
+        }
+        Ok(Coordinates::new(latitude, longitude))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub min: Coordinates,
+    pub max: Coordinates,
+}
+
+impl BoundingBox {
+    pub fn from_points<'a, I: IntoIterator<Item = &'a Coordinates>>(points: I) -> Option<Self> {
+        points.into_iter().fold(None, |bbox, p| {
+            Some(match bbox {
+                None => BoundingBox { min: *p, max: *p },
+                Some(BoundingBox { min, max }) => BoundingBox {
+                    min: Coordinates::new(
+                        min.latitude.min(p.latitude),
+                        min.longitude.min(p.longitude),
+                    ),
+                    max: Coordinates::new(
+                        max.latitude.max(p.latitude),
+                        max.longitude.max(p.longitude),
+                    ),
+                },
+            })
+        })
+    }
+
+    pub fn contains(&self, point: &Coordinates) -> bool {
+        (self.min.latitude..=self.max.latitude).contains(&point.latitude)
+            && (self.min.longitude..=self.max.longitude).contains(&point.longitude)
+    }
+
+    // point of the box closest to point, point itself when inside
+    pub fn nearest(&self, point: &Coordinates) -> Coordinates {
+        Coordinates::new(
+            point.latitude.clamp(self.min.latitude, self.max.latitude),
+            point
+                .longitude
+                .clamp(self.min.longitude, self.max.longitude),
+        )
+    }
+
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.latitude <= other.max.latitude
+            && other.min.latitude <= self.max.latitude
+            && self.min.longitude <= other.max.longitude
+            && other.min.longitude <= self.max.longitude
+    }
+
+    pub fn center(&self) -> Coordinates {
+        Coordinates::new(
+            (self.min.latitude + self.max.latitude) / 2.,
+            (self.min.longitude + self.max.longitude) / 2.,
+        )
+    }
+}
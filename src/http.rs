@@ -0,0 +1,211 @@
+use std::cell::Cell;
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Context;
+use reqwest::ResponseBuilderExt;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::retry::RetryPolicy;
+use crate::scheduler::{Scheduler, SchedulerConfig};
+
+pub static USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+tokio::task_local! {
+    static ATTEMPTS: Cell<u32>;
+}
+
+// attempts made by Http::send while running future, retries included,
+// whether it succeeds or not; nested counts are added to the enclosing one
+pub async fn count_attempts<F: Future>(future: F) -> (u32, F::Output) {
+    let (attempts, output) = ATTEMPTS
+        .scope(Cell::new(0), async {
+            let output = future.await;
+            (ATTEMPTS.with(Cell::get), output)
+        })
+        .await;
+    let _ = ATTEMPTS.try_with(|x| x.set(x.get() + attempts));
+    (attempts, output)
+}
+
+// settings of the reqwest::Client shared by the ViteMaDose fetch and every provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    #[serde(with = "humantime_serde")]
+    pub timeout: Option<Duration>,
+    #[serde(with = "humantime_serde")]
+    pub connect_timeout: Option<Duration>,
+    pub user_agent: String,
+    // applied to http and https, e.g. "socks5://127.0.0.1:9050"
+    pub proxy: Option<String>,
+    pub pool_max_idle_per_host: usize,
+    #[serde(with = "humantime_serde")]
+    pub pool_idle_timeout: Option<Duration>,
+    pub scheduler: SchedulerConfig,
+    pub retry: RetryPolicy,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(30)),
+            connect_timeout: Some(Duration::from_secs(10)),
+            user_agent: USER_AGENT.to_owned(),
+            proxy: None,
+            pool_max_idle_per_host: 16,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            scheduler: SchedulerConfig::default(),
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+impl HttpConfig {
+    pub fn builder(&self) -> anyhow::Result<reqwest::ClientBuilder> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(&self.user_agent)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(self.pool_idle_timeout);
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+
+        Ok(builder)
+    }
+
+    pub fn build(&self) -> anyhow::Result<reqwest::Client> {
+        Ok(self.builder()?.build()?)
+    }
+}
+
+// the same response with its body read
+async fn buffered(response: reqwest::Response) -> anyhow::Result<reqwest::Response> {
+    let mut builder = http::Response::builder()
+        .status(response.status())
+        .version(response.version())
+        .url(response.url().clone());
+    if let Some(headers) = builder.headers_mut() {
+        *headers = response.headers().clone();
+    }
+    let body = response.bytes().await?;
+    Ok(builder.body(body)?.into())
+}
+
+// a client built once from HttpConfig, cheap to clone
+#[derive(Debug, Clone)]
+pub struct Http {
+    config: HttpConfig,
+    client: reqwest::Client,
+    scheduler: Scheduler,
+}
+
+impl Default for Http {
+    fn default() -> Self {
+        Http::new(HttpConfig::default()).expect("default http client")
+    }
+}
+
+impl Http {
+    pub fn new(config: HttpConfig) -> anyhow::Result<Self> {
+        let client = config.build()?;
+        let scheduler = Scheduler::new(config.scheduler.clone());
+        Ok(Self {
+            config,
+            client,
+            scheduler,
+        })
+    }
+
+    pub fn config(&self) -> &HttpConfig {
+        &self.config
+    }
+
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub fn scheduler(&self) -> &Scheduler {
+        &self.scheduler
+    }
+
+    // sends through the scheduler, whichever client the request was built with,
+    // retrying on connection errors and retryable statuses as per config.retry
+    // requests which aren't idempotent, such as slot claims and webhook posts, are sent once
+    pub async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> anyhow::Result<reqwest::Response> {
+        let (client, request) = request.build_split();
+        let request = request?;
+        let host = request.url().host_str().unwrap_or_default().to_owned();
+        let url = request.url().clone();
+        let policy = &self.config.retry;
+        let retryable = request.method().is_idempotent();
+
+        let mut request = Some(request);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let _ = ATTEMPTS.try_with(|x| x.set(x.get() + 1));
+            // bodies which are streams can't be sent twice
+            let (request, last) = match request.as_ref().and_then(|x| x.try_clone()) {
+                Some(x) if retryable && attempt < policy.max_attempts => (x, false),
+                _ => (request.take().expect("the last attempt returns"), true),
+            };
+
+            let permit = self.scheduler.acquire(&host).await;
+            let result = client.execute(request).await;
+
+            let wait = match &result {
+                _ if last => None,
+                Ok(response) => policy.wait(attempt, response),
+                Err(e) if RetryPolicy::is_retryable_error(e) => Some(policy.backoff(attempt)),
+                Err(_) => None,
+            };
+
+            match wait {
+                Some(wait) => {
+                    drop(permit);
+                    log::debug!(
+                        "attempt {} to {} failed, retrying in {:?}",
+                        attempt,
+                        url,
+                        wait
+                    );
+                    tokio::time::sleep(wait).await;
+                }
+                None => {
+                    let response =
+                        result.with_context(|| format!("{} after {} attempt(s)", url, attempt))?;
+                    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                        return Err(Error::RateLimited {
+                            host,
+                            retry_after: crate::retry::retry_after(&response),
+                        }
+                        .into());
+                    }
+                    // downloading the body counts towards the concurrency too
+                    let response = buffered(response)
+                        .await
+                        .with_context(|| format!("{} after {} attempt(s)", url, attempt))?;
+                    drop(permit);
+                    return Ok(response);
+                }
+            }
+        }
+    }
+
+    // same settings with its own cookie jar, not pooled with the shared client
+    pub fn cookie_client(&self) -> anyhow::Result<reqwest::Client> {
+        Ok(self.config.builder()?.cookie_store(true).build()?)
+    }
+}
@@ -1,14 +1,19 @@
-use serde::{Deserialize, Serialize};
-
 pub mod center;
 pub mod commune;
+pub mod config;
+pub mod department;
+pub mod error;
+pub mod format;
+pub mod geo;
+pub mod http;
+pub mod notify;
+pub mod retry;
+pub mod scan;
+pub mod scheduler;
 pub mod service;
 pub mod util;
+pub mod vitemadose;
+pub mod watch;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Department {
-    code_departement: String,
-    nom_departement: String,
-    code_region: f64,
-    nom_region: String,
-}
+pub use department::Department;
+pub use error::{Error, Result};
@@ -1,69 +1,287 @@
-use anyhow::Result;
-use futures::future::join_all;
-use log::info;
+mod cli;
 
-use covax::center::{CenterInfo, CentersInDepartment};
+use anyhow::Result;
+use chrono::Local;
+use clap::Parser;
+use cli_table::{print_stdout, Cell, Style, Table};
+use log::{info, warn};
 
-use cli_table::{print_stdout, WithTitle};
+use covax::commune::CommuneIndex;
+use covax::config::{Config, Profile};
+use covax::geo::Coordinates;
+use covax::http::Http;
+use covax::notify::email::Email;
+use covax::notify::webhook::{Webhook, WebhookConfig};
+use covax::notify::{notify_all, Alert, Notifier};
+use covax::scan::{scan, Search};
+use covax::service::doctolib::process_doctolib_center;
+use covax::service::ProviderRegistry;
+use covax::vitemadose::ViteMaDose;
+use covax::watch::{jittered, Change, ChangeKind, Watcher};
+use covax::Department;
 
-// static COVIDTRACKER: &str = "https://vitemadose.covidtracker.fr/";
-static GITLAB: &str = "https://vitemadose.gitlab.io/vitemadose/";
+use cli::{Cli, Command};
 
-// lazy_static::lazy_static! {
-//     static ref DEPTS: Vec<Department> = serde_json::from_str(include_str!("data/departements.json")).unwrap();
-// }
+// "lat,long" or a commune looked up on ViteMaDose or in the communes file
+async fn locate(vitemadose: &ViteMaDose, near: &str) -> Result<Coordinates> {
+    if let Ok(coordinates) = near.parse() {
+        return Ok(coordinates);
+    }
+    vitemadose
+        .search_communes(near)
+        .await?
+        .locate(near)
+        .and_then(|c| c.location())
+        .ok_or_else(|| anyhow::anyhow!("no commune found for {}", near))
+}
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    env_logger::init();
+async fn prepare(
+    vitemadose: &ViteMaDose,
+    query: &cli::Query,
+    profile: &Profile,
+) -> Result<(ProviderRegistry, Search)> {
+    let http = vitemadose.http();
+    let mut providers = ProviderRegistry::with_http(http);
+    providers.register(query.slots.doctolib(profile, http));
 
-    // departements around Paris
-    let depts = vec![75, 77, 78, 91, 92, 93, 94, 95];
-
-    // musee du Louvre geo-location
-    let (lat, long) = (48.864824, 2.334595);
-
-    let departments: Vec<anyhow::Result<CentersInDepartment>> = join_all(
-        // includes all main-land french departements
-        // (1..=95)
-        depts
-            .into_iter()
-            .map(|d: usize| async move {
-                Ok(reqwest::get(&format!("{}{:02}.json", GITLAB, d))
-                    .await?
-                    .json()
-                    .await?)
-            })
-            .collect::<Vec<_>>(),
-    )
-    .await;
-
-    info!(
-        "Parsed data of {} department(s).",
-        departments.iter().filter(|x| x.is_ok()).count()
+    let mut search = Search::new(
+        locate(vitemadose, &query.near(profile)).await?,
+        query.radius_km(profile),
     );
+    search.vaccines = query.slots.vaccines(profile);
+    search.chronodose = query.chronodose(profile);
+    search.departments = query.departments(profile);
 
-    let data: Vec<anyhow::Result<CenterInfo>> = join_all(
-        departments
-            .into_iter()
-            .filter_map(|x| x.ok())
-            .map(|x| x.centres_disponibles)
-            .flatten()
-            // .filter(|c| c.has_chronodose() && c.has_vaccine("pfizer"))
-            .map(|x| x.info(lat, long, 50000.))
-            .collect::<Vec<_>>(),
-    )
-    .await;
+    Ok((providers, search))
+}
+
+fn notifiers(
+    config: &Config,
+    webhooks: &[WebhookConfig],
+    desktop: bool,
+    http: &Http,
+) -> Result<Vec<Box<dyn Notifier>>> {
+    let mut notifiers: Vec<Box<dyn Notifier>> = vec![];
+    for webhook in config.notify.webhooks.iter().chain(webhooks) {
+        notifiers.push(Box::new(Webhook::new(webhook.clone(), http)));
+    }
+    if let Some(email) = &config.notify.email {
+        notifiers.push(Box::new(Email::new(email.clone())?));
+    }
+    if desktop || config.notify.desktop {
+        #[cfg(feature = "desktop")]
+        notifiers.push(Box::new(covax::notify::desktop::Desktop::default()));
+        #[cfg(not(feature = "desktop"))]
+        return Err(anyhow::anyhow!(
+            "desktop notifications need covax built with --features desktop"
+        ));
+    }
+    Ok(notifiers)
+}
+
+async fn search(
+    vitemadose: &ViteMaDose,
+    args: &cli::Search,
+    profile: &Profile,
+    config: &Config,
+) -> Result<()> {
+    let (providers, search) = prepare(vitemadose, &args.query, profile).await?;
+
+    let (data, report) = scan(vitemadose, &providers, &search).await;
 
     let mut data: Vec<_> = data
         .into_iter()
-        .flatten()
-        .filter(|x| x.n_slot > 0)
+        .filter(|x| args.all || x.n_slot > 0)
         .collect();
 
-    data.sort_by(|a, b| a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal));
+    data.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    args.format(profile)
+        .write(std::io::stdout().lock(), &data)?;
 
-    print_stdout(data.with_title())?;
+    if args.report_json {
+        eprintln!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        eprintln!("{}", report);
+    }
+
+    if args.notify {
+        let alerts: Vec<_> = data
+            .iter()
+            .filter(|x| x.n_slot > 0)
+            .map(Alert::from)
+            .collect();
+        let notifiers = notifiers(config, &[], false, vitemadose.http())?;
+        for e in notify_all(&notifiers, &alerts).await {
+            warn!("{:#}", e);
+        }
+    }
 
     Ok(())
 }
+
+fn print_change(change: &Change) {
+    let center = &change.center;
+    let next = center
+        .next_slot
+        .map(|x| format!(", next {}", x.format("%d/%m %H:%M")))
+        .unwrap_or_default();
+    let line = match change.kind {
+        ChangeKind::Appeared => format!(
+            "+ {} ({} km): {} slot(s){} {}",
+            center.name, center.distance, center.n_slot, next, center.url
+        ),
+        ChangeKind::Disappeared => format!("- {}: no more slots {}", center.name, center.url),
+        ChangeKind::Updated => format!(
+            "~ {}: {} slot(s), {} new, {} gone{} {}",
+            center.name,
+            center.n_slot,
+            change.new_slots.len(),
+            change.gone_slots.len(),
+            next,
+            center.url
+        ),
+    };
+    println!("[{}] {}", Local::now().format("%H:%M:%S"), line);
+}
+
+async fn watch(
+    vitemadose: &ViteMaDose,
+    args: &cli::Watch,
+    profile: &Profile,
+    config: &Config,
+) -> Result<()> {
+    let (providers, search) = prepare(vitemadose, &args.query, profile).await?;
+    let notifiers = notifiers(config, &args.webhook, args.desktop, vitemadose.http())?;
+    let interval = args.interval(profile);
+    let jitter = args.jitter(profile);
+
+    let mut watcher = Watcher::new();
+    loop {
+        let (data, report) = scan(vitemadose, &providers, &search).await;
+
+        if report.departments_failed.is_empty() {
+            let failed: Vec<_> = report
+                .centers_failed
+                .iter()
+                .map(|x| x.source.as_str())
+                .collect();
+            let changes = watcher.update(data, &failed);
+            for change in &changes {
+                if args.json {
+                    println!("{}", serde_json::to_string(change)?);
+                } else {
+                    print_change(change);
+                }
+            }
+            for e in notify_all(&notifiers, &Alert::from_changes(&changes)).await {
+                warn!("{:#}", e);
+            }
+        } else {
+            // their centers would all seem to have disappeared
+            warn!("keeping the previous results, some departments failed to load");
+        }
+
+        if report.departments_failed.is_empty() && report.centers_failed.is_empty() {
+            info!("{}", report);
+        } else {
+            eprintln!("{}", report);
+        }
+
+        tokio::time::sleep(jittered(interval, jitter)).await;
+    }
+}
+
+async fn center(args: &cli::CenterArgs, profile: &Profile, http: &Http) -> Result<()> {
+    let availability =
+        process_doctolib_center(&args.url, &args.slots.doctolib(profile, http)).await?;
+
+    let rows: Vec<_> = availability
+        .slots
+        .iter()
+        .map(|x| {
+            vec![
+                x.start.to_rfc2822().cell(),
+                x.motive.clone().unwrap_or_default().cell(),
+            ]
+        })
+        .collect();
+    print_stdout(
+        rows.table()
+            .title(vec!["Start".cell().bold(true), "Motive".cell().bold(true)]),
+    )?;
+    eprintln!(
+        "{} slot(s) for {}",
+        availability.n_slot(),
+        availability.motives.join(", ")
+    );
+
+    Ok(())
+}
+
+fn departments() -> Result<()> {
+    let rows: Vec<_> = Department::all()
+        .iter()
+        .map(|x| vec![x.code().cell(), x.name().cell(), x.region_name().cell()])
+        .collect();
+    print_stdout(rows.table().title(vec![
+        "Code".cell().bold(true),
+        "Name".cell().bold(true),
+        "Region".cell().bold(true),
+    ]))?;
+    Ok(())
+}
+
+async fn communes(vitemadose: &ViteMaDose, query: &str) -> Result<()> {
+    let index = vitemadose.search_communes(query).await?;
+
+    let rows: Vec<_> = index
+        .search(query)
+        .into_iter()
+        .map(|x| {
+            vec![
+                x.postcode().cell(),
+                x.insee().cell(),
+                x.name().cell(),
+                x.department()
+                    .map(|d| d.to_string())
+                    .unwrap_or_default()
+                    .cell(),
+                x.location()
+                    .map(|c| c.to_string())
+                    .unwrap_or_default()
+                    .cell(),
+            ]
+        })
+        .collect();
+    print_stdout(rows.table().title(vec![
+        "Postcode".cell().bold(true),
+        "INSEE".cell().bold(true),
+        "Name".cell().bold(true),
+        "Department".cell().bold(true),
+        "Location".cell().bold(true),
+    ]))?;
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+
+    let cli = Cli::parse();
+    let config = cli.load_config()?;
+    let profile = config.profile(cli.profile.as_deref())?;
+    let http = Http::new(cli.http_config(&config))?;
+    let mut vitemadose = ViteMaDose::with_http(&cli.base_url(&config), &http);
+    if let Some(path) = cli.communes_file(&config) {
+        vitemadose = vitemadose.with_communes(CommuneIndex::load(path)?);
+    }
+
+    match &cli.command {
+        Command::Search(args) => search(&vitemadose, args, &profile, &config).await,
+        Command::Watch(args) => watch(&vitemadose, args, &profile, &config).await,
+        Command::Center(args) => center(args, &profile, &http).await,
+        Command::Departments => departments(),
+        Command::Communes { query } => communes(&vitemadose, query).await,
+    }
+}
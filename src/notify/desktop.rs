@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use notify_rust::{Notification, Timeout};
+
+use super::{Alert, Notifier};
+
+// popups shown at once, the others are summed up in a last one
+const POPUPS: usize = 3;
+
+// "default" is the action of a click on the popup itself
+fn open_on_click(notification: &Notification, url: String) -> anyhow::Result<()> {
+    let handle = notification.show()?;
+    // waits for the popup to be clicked or closed, on its own thread
+    std::thread::spawn(move || {
+        handle.wait_for_action(|action| {
+            if action == "default" {
+                if let Err(e) = std::process::Command::new("xdg-open").arg(&url).spawn() {
+                    log::warn!("couldn't open {}: {}", url, e);
+                }
+            }
+        })
+    });
+    Ok(())
+}
+
+// org.freedesktop.Notifications popups, clicking one opens the center booking page
+#[derive(Debug, Clone, Default)]
+pub struct Desktop {
+    // milliseconds, server default if None
+    pub timeout: Option<u32>,
+}
+
+impl Desktop {
+    fn notification(&self, summary: &str, body: &str) -> Notification {
+        let mut notification = Notification::new();
+        notification
+            .appname("covax")
+            .summary(summary)
+            .body(body)
+            .timeout(self.timeout.map_or(Timeout::Default, Timeout::Milliseconds));
+        notification
+    }
+}
+
+#[async_trait]
+impl Notifier for Desktop {
+    fn name(&self) -> &str {
+        "desktop"
+    }
+
+    async fn notify(&self, alerts: &[Alert]) -> anyhow::Result<()> {
+        let mut popups: Vec<_> = alerts
+            .iter()
+            .take(POPUPS)
+            .map(|x| {
+                let mut notification = self.notification(&x.name, &x.summary());
+                notification.action("default", "Open");
+                (notification, Some(x.url.clone()))
+            })
+            .collect();
+        if alerts.len() > POPUPS {
+            let others: Vec<_> = alerts[POPUPS..].iter().map(|x| x.name.as_str()).collect();
+            let summary = format!("{} more center(s) with new slots", others.len());
+            popups.push((self.notification(&summary, &others.join("\n")), None));
+        }
+
+        // the d-bus calls block
+        tokio::task::spawn_blocking(move || {
+            for (notification, url) in popups {
+                match url {
+                    Some(url) => open_on_click(&notification, url)?,
+                    None => {
+                        notification.show()?;
+                    }
+                }
+            }
+            Ok(())
+        })
+        .await?
+    }
+}
@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+
+use super::{escape_html, Alert, Notifier};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Security {
+    // upgrades the connection, port 587 by default
+    #[default]
+    StartTls,
+    // implicit tls, port 465 by default
+    Tls,
+    // unencrypted, port 25 by default, for local relays only
+    Plain,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub security: Security,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    // "covax <covax@example.org>"
+    pub from: String,
+    pub to: Vec<String>,
+    #[serde(default, with = "humantime_serde")]
+    pub timeout: Option<Duration>,
+}
+
+impl SmtpConfig {
+    pub fn new(host: &str, from: &str, to: &[&str]) -> Self {
+        Self {
+            host: host.to_owned(),
+            port: None,
+            security: Security::default(),
+            username: None,
+            password: None,
+            from: from.to_owned(),
+            to: to.iter().map(|x| x.to_string()).collect(),
+            timeout: None,
+        }
+    }
+}
+
+pub fn subject(alerts: &[Alert]) -> String {
+    match alerts {
+        [alert] => format!("New vaccination slots at {}", alert.name),
+        _ => format!("New vaccination slots in {} centers", alerts.len()),
+    }
+}
+
+pub fn text_digest(alerts: &[Alert]) -> String {
+    alerts
+        .iter()
+        .map(|x| format!("{}\n{}\n{}\n", x.name, x.summary(), x.url))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn html_digest(alerts: &[Alert]) -> String {
+    let items: Vec<_> = alerts
+        .iter()
+        .map(|x| {
+            format!(
+                "<li><a href=\"{}\">{}</a><br>{}</li>",
+                escape_html(&x.url),
+                escape_html(&x.name),
+                escape_html(&x.summary())
+            )
+        })
+        .collect();
+    format!(
+        "<html><body><p>{}</p><ul>{}</ul></body></html>",
+        escape_html(&subject(alerts)),
+        items.concat()
+    )
+}
+
+// sends a digest of the alerts to every recipient in a single message
+#[derive(Clone)]
+pub struct Email {
+    config: SmtpConfig,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl Email {
+    pub fn new(config: SmtpConfig) -> anyhow::Result<Self> {
+        let mut builder = match config.security {
+            Security::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?
+            }
+            Security::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+            Security::Plain => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+            }
+        };
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+        if let Some(username) = &config.username {
+            let password = config.password.clone().unwrap_or_default();
+            builder = builder.credentials(Credentials::new(username.clone(), password));
+        }
+        if config.timeout.is_some() {
+            builder = builder.timeout(config.timeout);
+        }
+        Ok(Self {
+            transport: builder.build(),
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &SmtpConfig {
+        &self.config
+    }
+
+    pub fn message(&self, alerts: &[Alert]) -> anyhow::Result<Message> {
+        if self.config.to.is_empty() {
+            return Err(anyhow::anyhow!("email without recipients"));
+        }
+        let mut builder = Message::builder()
+            .from(self.config.from.parse::<Mailbox>()?)
+            .subject(subject(alerts));
+        for to in &self.config.to {
+            builder = builder.to(to.parse::<Mailbox>()?);
+        }
+        Ok(builder.multipart(MultiPart::alternative_plain_html(
+            text_digest(alerts),
+            html_digest(alerts),
+        ))?)
+    }
+}
+
+#[async_trait]
+impl Notifier for Email {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    async fn notify(&self, alerts: &[Alert]) -> anyhow::Result<()> {
+        self.transport.send(self.message(alerts)?).await?;
+        Ok(())
+    }
+}
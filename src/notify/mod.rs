@@ -0,0 +1,113 @@
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+
+use crate::center::CenterInfo;
+use crate::service::Slot;
+use crate::watch::Change;
+
+#[cfg(feature = "desktop")]
+pub mod desktop;
+pub mod email;
+pub mod webhook;
+
+// slot times listed in the one line summaries
+const SUMMARY_SLOTS: usize = 5;
+
+// a center with new slots, as sent by notifiers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    pub name: String,
+    pub address: String,
+    pub distance_km: f64,
+    pub url: String,
+    pub vaccines: Vec<String>,
+    pub slot_count: usize,
+    // the new ones, empty for providers which only report a count
+    pub slots: Vec<DateTime<FixedOffset>>,
+}
+
+impl Alert {
+    pub fn new(center: &CenterInfo, slots: &[Slot]) -> Self {
+        let mut slots: Vec<_> = slots.iter().map(|x| x.start).collect();
+        slots.sort();
+        Self {
+            name: center.name.clone(),
+            address: center.address.clone(),
+            distance_km: center.distance,
+            url: center.url.clone(),
+            vaccines: center.vaccines.clone(),
+            slot_count: center.n_slot,
+            slots,
+        }
+    }
+
+    // centers with slots which weren't there on the previous run
+    pub fn from_changes(changes: &[Change]) -> Vec<Alert> {
+        changes
+            .iter()
+            .filter(|x| x.is_new_availability())
+            .map(|x| Alert::new(&x.center, &x.new_slots))
+            .collect()
+    }
+
+    // "address, 2.5 km, pfizer, 3 slot(s): 01/06 10:00, 01/06 10:15"
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{}, {} km", self.address, self.distance_km);
+        if !self.vaccines.is_empty() {
+            summary += &format!(", {}", self.vaccines.join(" / "));
+        }
+        summary += &format!(", {} slot(s)", self.slot_count);
+        if !self.slots.is_empty() {
+            let times: Vec<_> = self
+                .slots
+                .iter()
+                .take(SUMMARY_SLOTS)
+                .map(|x| x.format("%d/%m %H:%M").to_string())
+                .collect();
+            summary += &format!(": {}", times.join(", "));
+            if self.slots.len() > SUMMARY_SLOTS {
+                summary += ", ...";
+            }
+        }
+        summary
+    }
+}
+
+pub(crate) fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl From<&CenterInfo> for Alert {
+    fn from(center: &CenterInfo) -> Self {
+        Alert::new(center, &center.slots)
+    }
+}
+
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &str;
+
+    // never called without alerts
+    async fn notify(&self, alerts: &[Alert]) -> anyhow::Result<()>;
+}
+
+// notifies everyone, returns the failures
+pub async fn notify_all(notifiers: &[Box<dyn Notifier>], alerts: &[Alert]) -> Vec<anyhow::Error> {
+    if alerts.is_empty() {
+        return vec![];
+    }
+    join_all(notifiers.iter().map(|x| async move {
+        x.notify(alerts)
+            .await
+            .map_err(|e| e.context(format!("{} notification", x.name())))
+    }))
+    .await
+    .into_iter()
+    .filter_map(|x| x.err())
+    .collect()
+}
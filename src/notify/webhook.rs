@@ -0,0 +1,251 @@
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{escape_html, Alert, Notifier};
+use crate::http::Http;
+
+// slack accepts 50 blocks and discord 10 embeds per message
+const SLACK_BLOCKS: usize = 50;
+const DISCORD_EMBEDS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookKind {
+    // {"event": "new_availability", "centers": [<Alert>, ...]}
+    #[default]
+    Json,
+    // incoming webhook
+    Slack,
+    Discord,
+    // client-server api, url being the homeserver
+    Matrix,
+}
+
+impl fmt::Display for WebhookKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookKind::Json => write!(f, "json"),
+            WebhookKind::Slack => write!(f, "slack"),
+            WebhookKind::Discord => write!(f, "discord"),
+            WebhookKind::Matrix => write!(f, "matrix"),
+        }
+    }
+}
+
+impl FromStr for WebhookKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(WebhookKind::Json),
+            "slack" => Ok(WebhookKind::Slack),
+            "discord" => Ok(WebhookKind::Discord),
+            "matrix" => Ok(WebhookKind::Matrix),
+            _ => Err(anyhow::anyhow!("unknown webhook kind: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub kind: WebhookKind,
+    // matrix room id, such as "!abc:matrix.org"
+    pub room: Option<String>,
+    // sent as a bearer token, required by matrix
+    pub access_token: Option<String>,
+}
+
+impl WebhookConfig {
+    pub fn new(kind: WebhookKind, url: &str) -> Self {
+        Self {
+            url: url.to_owned(),
+            kind,
+            room: None,
+            access_token: None,
+        }
+    }
+}
+
+// "<url>" for json, or "<kind>=<url>", matrix needing a room and a token
+impl FromStr for WebhookConfig {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.split_once('=') {
+            Some((kind, url)) if !kind.contains(':') && !kind.contains('/') => {
+                match kind.parse()? {
+                    WebhookKind::Matrix => Err(anyhow::anyhow!(
+                        "matrix webhooks need a room and an access token, \
+                         set them under [[notify.webhooks]] in the configuration file"
+                    )),
+                    kind => Ok(WebhookConfig::new(kind, url)),
+                }
+            }
+            _ => Ok(WebhookConfig::new(WebhookKind::Json, s)),
+        }
+    }
+}
+
+// slack mrkdwn only needs these three
+fn escape_slack(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn headline(alerts: &[Alert]) -> String {
+    format!("{} center(s) with new slots", alerts.len())
+}
+
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    config: WebhookConfig,
+    http: Http,
+}
+
+impl Webhook {
+    pub fn new(config: WebhookConfig, http: &Http) -> Self {
+        Self {
+            config,
+            http: http.clone(),
+        }
+    }
+
+    pub fn config(&self) -> &WebhookConfig {
+        &self.config
+    }
+
+    pub fn payload(&self, alerts: &[Alert]) -> Value {
+        match self.config.kind {
+            WebhookKind::Json => json!({
+                "event": "new_availability",
+                "centers": alerts,
+            }),
+            WebhookKind::Slack => {
+                let blocks: Vec<_> = alerts
+                    .iter()
+                    .take(SLACK_BLOCKS)
+                    .map(|x| {
+                        json!({
+                            "type": "section",
+                            "text": {
+                                "type": "mrkdwn",
+                                "text": format!(
+                                    "*<{}|{}>*\n{}",
+                                    x.url,
+                                    escape_slack(&x.name),
+                                    escape_slack(&x.summary())
+                                ),
+                            },
+                        })
+                    })
+                    .collect();
+                json!({ "text": headline(alerts), "blocks": blocks })
+            }
+            WebhookKind::Discord => {
+                let embeds: Vec<_> = alerts
+                    .iter()
+                    .take(DISCORD_EMBEDS)
+                    .map(|x| {
+                        json!({
+                            "title": x.name,
+                            "url": x.url,
+                            "description": x.summary(),
+                        })
+                    })
+                    .collect();
+                json!({ "content": headline(alerts), "embeds": embeds })
+            }
+            WebhookKind::Matrix => {
+                let body: Vec<_> = alerts
+                    .iter()
+                    .map(|x| format!("{} {}\n{}", x.name, x.url, x.summary()))
+                    .collect();
+                let html: Vec<_> = alerts
+                    .iter()
+                    .map(|x| {
+                        format!(
+                            "<li><a href=\"{}\">{}</a><br>{}</li>",
+                            escape_html(&x.url),
+                            escape_html(&x.name),
+                            escape_html(&x.summary())
+                        )
+                    })
+                    .collect();
+                json!({
+                    "msgtype": "m.notice",
+                    "body": format!("{}\n\n{}", headline(alerts), body.join("\n\n")),
+                    "format": "org.matrix.custom.html",
+                    "formatted_body": format!(
+                        "<p>{}</p><ul>{}</ul>",
+                        headline(alerts),
+                        html.concat()
+                    ),
+                })
+            }
+        }
+    }
+
+    fn request(&self, alerts: &[Alert]) -> anyhow::Result<reqwest::RequestBuilder> {
+        let client = self.http.client();
+        let request = match self.config.kind {
+            WebhookKind::Matrix => {
+                let room = self
+                    .config
+                    .room
+                    .as_deref()
+                    .ok_or_else(|| anyhow::anyhow!("matrix webhook without a room"))?;
+                // transaction ids make retries idempotent
+                let txn = format!(
+                    "covax-{}-{}",
+                    chrono::Utc::now().timestamp_millis(),
+                    rand::thread_rng().gen::<u32>()
+                );
+                let mut url = reqwest::Url::parse(&self.config.url)?;
+                url.path_segments_mut()
+                    .map_err(|_| anyhow::anyhow!("invalid homeserver: {}", self.config.url))?
+                    .pop_if_empty()
+                    .extend(&[
+                        "_matrix",
+                        "client",
+                        "r0",
+                        "rooms",
+                        room,
+                        "send",
+                        "m.room.message",
+                        &txn,
+                    ]);
+                client.put(url)
+            }
+            _ => client.post(&self.config.url),
+        };
+        let request = request.json(&self.payload(alerts));
+        Ok(match &self.config.access_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for Webhook {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn notify(&self, alerts: &[Alert]) -> anyhow::Result<()> {
+        self.http
+            .send(self.request(alerts)?)
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
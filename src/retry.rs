@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::{header::RETRY_AFTER, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    // including the first one, 1 disables retries
+    pub max_attempts: u32,
+    #[serde(with = "humantime_serde")]
+    pub initial_backoff: Duration,
+    #[serde(with = "humantime_serde")]
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    // fraction of the backoff randomly added or removed, in [0, 1]
+    #[serde(deserialize_with = "crate::util::deserialize_fraction")]
+    pub jitter: f64,
+    // longer Retry-After are not waited for, the response is returned as is
+    #[serde(with = "humantime_serde")]
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.,
+            jitter: 0.2,
+            max_retry_after: Duration::from_secs(120),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    // backoff after the given failed attempt, starting at 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self.initial_backoff.as_secs_f64()
+            * self.multiplier.powi(attempt.saturating_sub(1) as i32);
+        let jitter = self.jitter.clamp(0., 1.);
+        let factor = 1. + jitter * rand::thread_rng().gen_range(-1.0..=1.0);
+        Duration::from_secs_f64(
+            (backoff * factor)
+                .min(self.max_backoff.as_secs_f64())
+                .max(0.),
+        )
+    }
+
+    pub fn is_retryable(status: StatusCode) -> bool {
+        matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::GATEWAY_TIMEOUT
+        )
+    }
+
+    pub fn is_retryable_error(error: &reqwest::Error) -> bool {
+        error.is_timeout() || error.is_connect() || error.is_request()
+    }
+
+    // how long to wait before the next attempt, None to stop retrying
+    pub fn wait(&self, attempt: u32, response: &Response) -> Option<Duration> {
+        if attempt >= self.max_attempts || !Self::is_retryable(response.status()) {
+            return None;
+        }
+        match retry_after(response) {
+            Some(wait) if wait > self.max_retry_after => None,
+            Some(wait) => Some(wait),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+}
+
+// Retry-After in seconds or as an http date
+pub fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    value
+        .parse::<u64>()
+        .map(Duration::from_secs)
+        .ok()
+        .or_else(|| {
+            chrono::DateTime::parse_from_rfc2822(value)
+                .ok()?
+                .signed_duration_since(chrono::Utc::now())
+                .to_std()
+                .ok()
+                .or(Some(Duration::from_secs(0)))
+        })
+}
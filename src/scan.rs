@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use serde::{Serialize, Serializer};
+
+use crate::center::{Center, CenterInfo, CenterResults};
+use crate::department::{Department, DepartmentCode};
+use crate::error::Error;
+use crate::geo::Coordinates;
+use crate::http::count_attempts;
+use crate::service::doctolib::motive::Vaccine;
+use crate::service::ProviderRegistry;
+use crate::vitemadose::ViteMaDose;
+
+fn as_secs<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Failure {
+    // department code or center url
+    pub source: String,
+    pub kind: &'static str,
+    pub message: String,
+    // requests sent before giving up, retries included
+    pub attempts: u32,
+}
+
+impl Failure {
+    fn new(source: String, error: &Error, attempts: u32) -> Self {
+        Self {
+            source,
+            kind: error.kind(),
+            message: error.to_string(),
+            attempts,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScanReport {
+    pub departments_fetched: Vec<DepartmentCode>,
+    pub departments_failed: Vec<Failure>,
+    // requests made for the fetched departments, retries included
+    pub department_attempts: u32,
+    pub centers_scanned: usize,
+    pub centers_found: usize,
+    // left out by the search filters
+    pub centers_skipped: usize,
+    pub centers_failed: Vec<Failure>,
+    // requests made for every center, failed ones included
+    pub center_attempts: u32,
+    // over departments and centers
+    pub errors_by_kind: BTreeMap<&'static str, usize>,
+    #[serde(serialize_with = "as_secs")]
+    pub elapsed: Duration,
+}
+
+impl ScanReport {
+    fn department_failed(&mut self, code: DepartmentCode, error: &Error, attempts: u32) {
+        *self.errors_by_kind.entry(error.kind()).or_default() += 1;
+        self.departments_failed
+            .push(Failure::new(code.to_string(), error, attempts));
+    }
+
+    fn center_failed(&mut self, url: String, error: &Error, attempts: u32) {
+        *self.errors_by_kind.entry(error.kind()).or_default() += 1;
+        self.centers_failed.push(Failure::new(url, error, attempts));
+    }
+}
+
+// one line summary
+impl fmt::Display for ScanReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} department(s), {} center(s) scanned in {:.1}s: {} found, {} skipped, {} failed",
+            self.departments_fetched.len(),
+            self.centers_scanned,
+            self.elapsed.as_secs_f64(),
+            self.centers_found,
+            self.centers_skipped,
+            self.centers_failed.len(),
+        )?;
+        if !self.departments_failed.is_empty() {
+            write!(
+                f,
+                ", {} department(s) failed to load",
+                self.departments_failed.len()
+            )?;
+        }
+        if !self.errors_by_kind.is_empty() {
+            let kinds: Vec<_> = self
+                .errors_by_kind
+                .iter()
+                .map(|(kind, n)| format!("{}: {}", kind, n))
+                .collect();
+            write!(f, " ({})", kinds.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+// centers within radius_km of origin, optionally restricted by vaccine or to chronodoses
+#[derive(Debug, Clone, PartialEq)]
+pub struct Search {
+    pub origin: Coordinates,
+    pub radius_km: f64,
+    // any of them, empty accepts every center
+    pub vaccines: Vec<Vaccine>,
+    pub chronodose: bool,
+    // looked up instead of the departments around origin
+    pub departments: Vec<DepartmentCode>,
+}
+
+impl Search {
+    pub fn new(origin: Coordinates, radius_km: f64) -> Self {
+        Self {
+            origin,
+            radius_km,
+            vaccines: vec![],
+            chronodose: false,
+            departments: vec![],
+        }
+    }
+
+    // filters which don't need the provider, distance apart
+    pub fn matches(&self, center: &Center) -> bool {
+        (self.vaccines.is_empty()
+            || self
+                .vaccines
+                .iter()
+                .any(|v| center.has_vaccine(&v.to_string())))
+            && (!self.chronodose || center.has_chronodose())
+    }
+}
+
+// looks up the centers of the search, in the departments around its origin by default
+pub async fn scan(
+    vitemadose: &ViteMaDose,
+    providers: &ProviderRegistry,
+    search: &Search,
+) -> (Vec<CenterInfo>, ScanReport) {
+    let start = Instant::now();
+    let mut report = ScanReport::default();
+
+    let codes: Vec<_> = if search.departments.is_empty() {
+        Department::around(&search.origin, search.radius_km)
+            .into_iter()
+            .map(|d| d.code())
+            .collect()
+    } else {
+        search.departments.clone()
+    };
+
+    let mut centers = vec![];
+    let departments = join_all(
+        codes
+            .iter()
+            .map(|&code| count_attempts(vitemadose.department(code))),
+    )
+    .await;
+    for (code, (attempts, result)) in codes.iter().zip(departments) {
+        match result {
+            Ok(department) => {
+                report.departments_fetched.push(*code);
+                report.department_attempts += department.attempts;
+                centers.extend(department.centres_disponibles);
+            }
+            Err(e) => {
+                let error = Error::provider("vitemadose", e);
+                log::warn!("department {}: {}", code, error);
+                report.department_failed(*code, &error, attempts);
+            }
+        }
+    }
+
+    report.centers_scanned = centers.len();
+    let (centers, skipped): (Vec<_>, Vec<_>) = centers.into_iter().partition(|c| search.matches(c));
+
+    let results = join_all(centers.into_iter().map(|center| async move {
+        let url = center.url().to_owned();
+        let (attempts, info) =
+            count_attempts(center.info(search.origin, search.radius_km, providers)).await;
+        (url, attempts, info)
+    }))
+    .await;
+
+    for (url, attempts, result) in &results {
+        report.center_attempts += attempts;
+        match result {
+            Err(e) if !e.is_filtered() => {
+                log::warn!("{}: {}", url, e);
+                report.center_failed(url.clone(), e, *attempts);
+            }
+            _ => {}
+        }
+    }
+
+    let results: CenterResults = results.into_iter().map(|(_, _, x)| x).collect();
+    report.centers_found = results.found.len();
+    report.centers_skipped = skipped.len() + results.filtered.len();
+    report.elapsed = start.elapsed();
+
+    (results.found, report)
+}
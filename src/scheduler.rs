@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+// token bucket: `burst` requests at once, refilled at `per_second`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub per_second: f64,
+    pub burst: f64,
+}
+
+impl RateLimit {
+    pub fn new(per_second: f64, burst: f64) -> Self {
+        Self { per_second, burst }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    // requests in flight at once, over all hosts
+    pub concurrency: usize,
+    // hosts without their own limit, unlimited if None
+    pub rate_limit: Option<RateLimit>,
+    // keyed by domain, also applies to its subdomains
+    pub hosts: HashMap<String, RateLimit>,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        let hosts = vec![
+            ("doctolib.fr".to_owned(), RateLimit::new(5., 10.)),
+            ("gitlab.io".to_owned(), RateLimit::new(10., 20.)),
+        ];
+        Self {
+            concurrency: 16,
+            rate_limit: None,
+            hosts: hosts.into_iter().collect(),
+        }
+    }
+}
+
+impl SchedulerConfig {
+    pub fn rate_limit(&self, host: &str) -> Option<RateLimit> {
+        self.bucket(host).map(|(_, limit)| limit)
+    }
+
+    // the domain whose limit applies to host, shared by its subdomains,
+    // or host itself under the default limit
+    fn bucket<'a>(&'a self, host: &'a str) -> Option<(&'a str, RateLimit)> {
+        self.hosts
+            .iter()
+            .filter(|(domain, _)| {
+                host == domain.as_str()
+                    || host
+                        .strip_suffix(domain.as_str())
+                        .is_some_and(|x| x.ends_with('.'))
+            })
+            .max_by_key(|(domain, _)| domain.len())
+            .map(|(domain, limit)| (domain.as_str(), *limit))
+            .or_else(|| self.rate_limit.map(|limit| (host, limit)))
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    tokens: f64,
+    last: Instant,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst,
+            last: Instant::now(),
+        }
+    }
+
+    // takes a token, possibly ahead of time, and returns how long to wait for it
+    fn take(&mut self) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.tokens = (self.tokens + elapsed * self.limit.per_second).min(self.limit.burst) - 1.;
+        if self.tokens >= 0. || self.limit.per_second <= 0. {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.limit.per_second)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    config: SchedulerConfig,
+    semaphore: Semaphore,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+// bounds concurrent requests and rate limits them per host, cheap to clone
+// a permit is held until the response body has been read, see Http::send
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    inner: Arc<Inner>,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new(SchedulerConfig::default())
+    }
+}
+
+impl Scheduler {
+    pub fn new(config: SchedulerConfig) -> Self {
+        let semaphore = Semaphore::new(config.concurrency.max(1));
+        Self {
+            inner: Arc::new(Inner {
+                config,
+                semaphore,
+                buckets: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &SchedulerConfig {
+        &self.inner.config
+    }
+
+    // waits for the host's rate limit, then for a free slot
+    pub async fn acquire(&self, host: &str) -> SemaphorePermit<'_> {
+        let wait = self.inner.config.bucket(host).map(|(key, limit)| {
+            self.inner
+                .buckets
+                .lock()
+                .unwrap()
+                .entry(key.to_owned())
+                .or_insert_with(|| Bucket::new(limit))
+                .take()
+        });
+
+        if let Some(wait) = wait.filter(|x| !x.is_zero()) {
+            log::debug!("rate limited {} for {:?}", host, wait);
+            tokio::time::sleep(wait).await;
+        }
+
+        self.inner
+            .semaphore
+            .acquire()
+            .await
+            .expect("scheduler semaphore is never closed")
+    }
+}
@@ -1,6 +1,6 @@
-use anyhow::Result;
-use chrono::Utc;
-use chrono::{DateTime, FixedOffset, NaiveDateTime};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::NaiveDate;
 use futures::{
     future::join_all,
     stream::{self, StreamExt},
@@ -8,38 +8,134 @@ use futures::{
 use log::info;
 use rand::Rng;
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+use crate::http::Http;
+
+use super::{Availability, DateRange, Slot, SlotPair, SlotProvider};
+
+pub mod model;
+pub mod motive;
+
+use model::{AvailabilityResponse, BookingResponse};
+use motive::MotiveSelector;
+
+pub static DOCTOLIB: &str = "https://www.doctolib.fr";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    // counts the slots listed by availabilities.json, never POSTs
+    #[default]
+    ReadOnly,
+    // confirms every slot through appointments.json, which temporarily claims it
+    Claim,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecondDose {
+    #[default]
+    Ignore,
+    // looks up a second dose slot for every first dose slot
+    Pair,
+    // as Pair, and drops first dose slots without a second dose slot
+    Require,
+}
+
+impl std::str::FromStr for SecondDose {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "ignore" => Ok(SecondDose::Ignore),
+            "pair" => Ok(SecondDose::Pair),
+            "require" => Ok(SecondDose::Require),
+            _ => Err(anyhow::anyhow!("unknown second dose mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Doctolib {
+    pub range: DateRange,
+    pub mode: Mode,
+    pub motives: MotiveSelector,
+    pub second_dose: SecondDose,
+    // days after the first dose to start looking for the second one
+    pub second_dose_delay: usize,
+    // without trailing slash, replaced by a stand-in in tests
+    pub base_url: String,
+    pub http: Http,
+}
+
+impl Default for Doctolib {
+    fn default() -> Self {
+        Self {
+            range: DateRange::default(),
+            mode: Mode::default(),
+            motives: MotiveSelector::pfizer_first_dose(),
+            second_dose: SecondDose::default(),
+            second_dose_delay: 21,
+            base_url: DOCTOLIB.to_owned(),
+            http: Http::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl SlotProvider for Doctolib {
+    fn name(&self) -> &str {
+        "doctolib"
+    }
+
+    fn detect(&self, center: &crate::center::Center) -> bool {
+        center.plateforme() == Some("Doctolib") || center.url().contains("doctolib")
+    }
+
+    async fn availability(&self, center: &crate::center::Center) -> Result<Availability> {
+        process_doctolib_center(center.url(), self).await
+    }
+}
 
 #[derive(Debug, Clone)]
 struct Center {
     agenda_ids: String,
     practice_ids: String,
     visit_motive_ids: String,
+    base_url: String,
+    http: Http,
 }
 
-fn gen_random_limit() -> String {
-    rand::thread_rng().gen_range::<usize, _>(4..=4).to_string()
-}
-
-fn motive_filter(name: &str) -> bool {
-    let name = name.replace("19", "").to_lowercase();
-    name.contains('1') && name.contains("pfizer")
+fn gen_random_limit() -> usize {
+    rand::thread_rng().gen_range::<usize, _>(4..=4)
 }
 
 impl Center {
-    fn new(agenda_ids: String, practice_ids: String, visit_motive_ids: String) -> Self {
+    fn new(
+        agenda_ids: String,
+        practice_ids: String,
+        visit_motive_ids: String,
+        config: &Doctolib,
+    ) -> Self {
         Center {
             agenda_ids,
             practice_ids,
             visit_motive_ids,
+            base_url: config.base_url.clone(),
+            http: config.http.clone(),
         }
     }
 
-    async fn check_availablity(&self, start_date: &str) -> Result<Value> {
-        let client = reqwest::Client::builder().build()?;
+    async fn check_availablity(
+        &self,
+        start_date: &str,
+        limit: usize,
+    ) -> Result<AvailabilityResponse> {
+        let client = self.http.client();
 
-        let limit = gen_random_limit();
+        let limit = limit.to_string();
         let query_params = vec![
             ("start_date", start_date),
             ("visit_motive_ids", self.visit_motive_ids.as_str()),
@@ -50,24 +146,70 @@ impl Center {
             ("limit", limit.as_str()),
         ];
 
-        let response = client
-            .get("https://www.doctolib.fr/availabilities.json")
-            .query(&query_params)
-            .send()
-            .await?;
+        let response = self
+            .http
+            .send(
+                client
+                    .get(format!("{}/availabilities.json", self.base_url))
+                    .query(&query_params),
+            )
+            .await?
+            .error_for_status()?;
 
         Ok(response.json().await?)
     }
 
+    // every slot in range, one request per window of `limit` days
+    // empty windows jump to the next_slot suggested by doctolib
+    async fn availabilities(&self, range: &DateRange) -> Result<Vec<model::Slot>> {
+        let end = range.end();
+        let mut date = range.start();
+        let mut slots = vec![];
+
+        while date < end {
+            let limit = gen_random_limit();
+            let start_date = date.format("%Y-%m-%d").to_string();
+            let response = self.check_availablity(&start_date, limit).await?;
+
+            let window: Vec<_> = response
+                .slots()
+                .filter(|x| {
+                    x.start()
+                        .is_some_and(|x| range.contains(x.naive_local().date()))
+                })
+                .cloned()
+                .collect();
+
+            info!("slots available from {}: {}", start_date, window.len());
+
+            let days = response.availabilities.len().max(1) as i64;
+            let mut next = date + chrono::Duration::days(days);
+
+            if window.is_empty() {
+                if let Some(next_slot) = response
+                    .next_slot
+                    .as_deref()
+                    .and_then(|x| NaiveDate::parse_from_str(x.get(..10)?, "%Y-%m-%d").ok())
+                {
+                    info!("next slot on {}", next_slot);
+                    next = next.max(next_slot);
+                }
+            }
+
+            slots.extend(window);
+            date = next;
+        }
+
+        Ok(slots)
+    }
+
     async fn check_appointment(&self, slots: &[&str]) -> Result<Vec<Value>> {
         // be careful when you check an appointment, it actually claims the slot for a cookie
         // without cookie-store, it will claim all the slots and make them unavailable temporarily
         // even with cookie, it will claim at least one slot
         // to unclaim even that one, claim any other unavailable slots (such as a time one hour ago)
 
-        let client = reqwest::Client::builder()
-            .cookie_store(true) // cookie-store is on
-            .build()?;
+        let client = self.http.cookie_client()?; // cookie-store is on
 
         let results = stream::iter(slots)
             .then(|slot| self._check_appointment(slot, &client))
@@ -76,15 +218,9 @@ impl Center {
 
         tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
 
-        let fake_slot = DateTime::parse_from_str(slots[0], "%Y-%m-%dT%H:%M:%S%.f%:z")
-            .or_else(|_| DateTime::parse_from_str(slots[0], "%Y-%m-%dT%H:%M:%S%.f%z"))
-            .or_else(|_| {
-                NaiveDateTime::parse_from_str(slots[0], "%Y-%m-%dT%H:%M:%S%.f")
-                    .map(|ndt| DateTime::from_utc(ndt, FixedOffset::east(2 * 3600)))
-            })
-            .ok()
-            .and_then(|x| x.checked_add_signed(chrono::Duration::days(10)))
-            .unwrap();
+        let fake_slot = crate::util::parse_date(slots[0])?
+            .checked_add_signed(chrono::Duration::days(10))
+            .ok_or_else(|| anyhow::anyhow!("invalid slot {}", slots[0]))?;
 
         // let fake_slot = Utc::now();
 
@@ -133,25 +269,25 @@ impl Center {
         });
 
         let request = client
-            .post("https://www.doctolib.fr/appointments.json")
+            .post(format!("{}/appointments.json", self.base_url))
             .json(&post_data);
 
         // info!("{:?}", request);
         // info!("{}", serde_json::to_string_pretty(&post_data).unwrap());
 
-        let response = request.send().await?;
+        let response = self.http.send(request).await?;
 
         Ok(response.json().await?)
     }
 
-    async fn _check_second_availablity(
+    async fn check_second_availablity(
         &self,
         second_start_date: &str,
         first_slot: &str,
-    ) -> Result<Value> {
-        let client = reqwest::Client::builder().build()?;
+    ) -> Result<AvailabilityResponse> {
+        let client = self.http.client();
 
-        let limit = gen_random_limit();
+        let limit = gen_random_limit().to_string();
         let query_params = vec![
             ("start_date", second_start_date),
             ("visit_motive_ids", self.visit_motive_ids.as_str()),
@@ -163,104 +299,147 @@ impl Center {
             ("limit", limit.as_str()),
         ];
 
-        let response = client
-            .get("https://www.doctolib.fr/second_shot_availabilities.json")
-            .query(&query_params)
-            .send()
-            .await?;
+        let response = self
+            .http
+            .send(
+                client
+                    .get(format!("{}/second_shot_availabilities.json", self.base_url))
+                    .query(&query_params),
+            )
+            .await?
+            .error_for_status()?;
 
-        info!("{}", response.url().to_string());
+        info!("{}", response.url());
 
         Ok(response.json().await?)
     }
+
+    // earliest second dose slot compatible with first_slot
+    async fn second_slot(&self, first_slot: &str, delay: usize) -> Result<Option<String>> {
+        let start_date = crate::util::parse_date(first_slot)?
+            .checked_add_signed(chrono::Duration::days(delay as i64))
+            .ok_or_else(|| anyhow::anyhow!("invalid slot {}", first_slot))?
+            .format("%Y-%m-%d")
+            .to_string();
+
+        let mut available = self
+            .check_second_availablity(&start_date, first_slot)
+            .await?;
+
+        if available.slots().next().is_none() {
+            if let Some(next_slot) = available
+                .next_slot
+                .take()
+                .and_then(|x| x.get(..10).map(str::to_owned))
+            {
+                available = self
+                    .check_second_availablity(&next_slot, first_slot)
+                    .await?;
+            }
+        }
+
+        let second = available.slots().next().map(|x| x.start_date().to_owned());
+        Ok(second)
+    }
 }
 
-pub async fn process_doctolib_center(center_url: &str, days: usize) -> anyhow::Result<usize> {
+pub async fn process_doctolib_center(
+    center_url: &str,
+    config: &Doctolib,
+) -> anyhow::Result<Availability> {
+    _process_doctolib_center(center_url, config)
+        .await
+        .with_context(|| format!("doctolib center {}", center_url))
+}
+
+async fn _process_doctolib_center(center_url: &str, config: &Doctolib) -> Result<Availability> {
     let url = reqwest::Url::parse(center_url)?;
-    let path_segs = url.path_segments().unwrap().collect::<Vec<_>>();
-    let center_id = path_segs.last().unwrap();
+    let center_id = url
+        .path_segments()
+        .and_then(|mut x| x.next_back())
+        .ok_or_else(|| anyhow::anyhow!("no center id in url"))?
+        .to_owned();
 
+    // pid=practice-<practice id>
     let practice_id = url
         .query_pairs()
         .find(|(x, _)| x == "pid")
-        .map(|(_, y)| y.split_once('-').unwrap().1.to_string())
-        .unwrap();
+        .and_then(|(_, y)| y.split_once('-').map(|(_, y)| y.to_string()))
+        .ok_or_else(|| anyhow::anyhow!("no practice id in url"))?;
 
     info!("Found center id: {}", center_id);
     info!("Found practice id: {}", practice_id);
 
-    let center_data: Value = reqwest::get(format!(
-        "https://www.doctolib.fr/booking/{}.json",
-        center_id
-    ))
-    .await?
-    .json()
-    .await?;
-
-    let visit_motive_ids: Vec<_> = center_data
-        .pointer("/data/visit_motives")
-        .and_then(|x| x.as_array())
-        .unwrap()
+    let http = &config.http;
+
+    let booking = http
+        .send(
+            http.client()
+                .get(format!("{}/booking/{}.json", config.base_url, center_id)),
+        )
+        .await?
+        .error_for_status()?
+        .json::<BookingResponse>()
+        .await?
+        .data;
+
+    let motives = config.motives.select(&booking.visit_motives);
+
+    motives
         .iter()
-        .filter(|x| motive_filter(x.pointer("/name").and_then(|x| x.as_str()).unwrap()))
-        .inspect(|x| {
-            info!(
-                "{:?}: {:?}",
-                x.pointer("/id")
-                    .and_then(|x| serde_json::to_string_pretty(x).ok())
-                    .unwrap(),
-                x.pointer("/name")
-                    .and_then(|x| serde_json::to_string_pretty(x).ok())
-                    .unwrap()
-            )
-        })
-        .filter_map(|x| x.pointer("/id").and_then(|x| x.as_u64()))
-        .collect();
+        .for_each(|x| info!("matched motive {}: {:?}", x.id, x.name));
+
+    let visit_motive_ids: HashSet<_> = motives.iter().map(|x| x.id).collect();
 
-    let mut agendas: Vec<_> = center_data
-        .pointer("/data/agendas")
-        .and_then(|x| x.as_array())
-        .unwrap()
+    let motive_ids = &visit_motive_ids;
+    let agendas: Vec<_> = booking
+        .agendas
         .iter()
-        // .filter(|x| x["booking_disabled"].as_bool().unwrap())
-        .map(|x| {
-            (
-                x["id"].as_u64().unwrap(),
-                x["visit_motive_ids_by_practice_id"].as_object().unwrap(),
-            )
-        })
-        .map(|(id, map)| {
-            map.into_iter()
+        // .filter(|x| !x.booking_disabled)
+        .flat_map(|agenda| {
+            agenda
+                .visit_motive_ids_by_practice_id
+                .iter()
                 .filter(|(k, _)| k == &&practice_id)
                 .map(move |(k, v)| {
                     let a: Vec<u64> = v
-                        .as_array()
-                        .unwrap()
                         .iter()
-                        .map(|x| x.as_u64().unwrap())
+                        .filter(|x| motive_ids.contains(x))
+                        .copied()
                         .collect();
-                    // a.retain(|x| (&visit_motive_ids).contains(&x));
-                    (id, k, a)
+                    (agenda.id, k, a)
                 })
         })
-        .flatten()
+        .filter(|(_, _, m)| !m.is_empty())
         .collect();
 
-    agendas
-        .iter_mut()
-        .for_each(|(_, _, m)| m.retain(|x| (&visit_motive_ids).contains(&x)));
+    let motive_names: HashMap<_, _> = motives
+        .into_iter()
+        .filter(|x| agendas.iter().any(|(_, _, m)| m.contains(&x.id)))
+        .map(|x| (x.id, x.name.clone()))
+        .collect();
 
-    agendas.retain(|(_, _, m)| !m.is_empty());
+    let motives: Vec<_> = motive_names.values().cloned().collect();
 
     if agendas.is_empty() {
-        return Ok(0);
+        return Ok(Availability::default());
     }
 
     let agenda_ids: HashSet<_> = agendas.iter().map(|(i, _, _)| i).collect();
 
+    // slots without steps only belong to an agenda or motive when there is a single one
+    let single_agenda = match agenda_ids.len() {
+        1 => agenda_ids.iter().next().map(|x| **x),
+        _ => None,
+    };
+    let single_motive = match motives.len() {
+        1 => motives.first().cloned(),
+        _ => None,
+    };
+
     let practice_ids: HashSet<_> = agendas.iter().map(|(_, k, _)| k).collect();
 
-    let visit_motive_ids: HashSet<_> = agendas.iter().map(|(_, _, v)| v).flatten().collect();
+    let visit_motive_ids: HashSet<_> = agendas.iter().flat_map(|(_, _, v)| v).collect();
 
     let agenda_ids = agenda_ids
         .into_iter()
@@ -279,93 +458,111 @@ pub async fn process_doctolib_center(center_url: &str, days: usize) -> anyhow::R
         .collect::<Vec<_>>()
         .join("-");
 
-    let center = Center::new(agenda_ids, practice_ids, visit_motive_ids);
+    let center = Center::new(agenda_ids, practice_ids, visit_motive_ids, config);
 
     info!("{:?}", center);
 
-    // Today's date "2021-05-21"
-    let date = Utc::now()
-        .checked_add_signed(chrono::Duration::days(days as i64))
-        .unwrap();
-
-    let test_date = date.format("%Y-%m-%d").to_string();
-
-    info!("Test date {}", test_date);
+    let available = center.availabilities(&config.range).await?;
 
-    let available = center.check_availablity(&test_date).await?;
+    info!(
+        "slots available from {} to {}: {}",
+        config.range.start(),
+        config.range.end(),
+        available.len()
+    );
 
-    let two_days_slots = available
-        .pointer("/availabilities")
-        .and_then(|x| x.as_array())
-        .map(|x| {
-            x.iter()
-                .take(2) // next two days
-                .filter_map(|y| y.pointer("/slots"))
-                .filter_map(|x| x.as_array())
-                .flatten()
-                .collect::<Vec<_>>()
-        })
-        .unwrap_or_default();
-
-    // if !two_days_slots.is_empty() {
-    //     info!("{:?}", center_url);
-    //     info!("{:?}", center_url);
-    //     info!("{:?}", two_days_slots);
-    // }
-
-    info!("slots available on {}: {}", test_date, two_days_slots.len());
-
-    let a: Vec<Result<Option<String>>> = join_all(
-        two_days_slots
-            .into_iter()
-            // .take(1)
-            .filter_map(|x| {
-                x.pointer("/start_date")
-                    .and_then(|x| x.as_str())
-                    .or_else(|| x.as_str())
-            })
-            .filter_map(|x| {
-                DateTime::parse_from_str(x, "%Y-%m-%dT%H:%M:%S%.f%:z")
-                    .or_else(|_| DateTime::parse_from_str(x, "%Y-%m-%dT%H:%M:%S%.f%z"))
-                    .or_else(|_| {
-                        NaiveDateTime::parse_from_str(x, "%Y-%m-%dT%H:%M:%S%.f")
-                            .map(|ndt| DateTime::from_utc(ndt, FixedOffset::east(2 * 3600)))
+    let slots: Vec<&model::Slot> = match config.mode {
+        Mode::ReadOnly => available.iter().collect(),
+        Mode::Claim => {
+            let a: Vec<Result<Option<&model::Slot>>> = join_all(
+                available
+                    .iter()
+                    .map(|s| (center.clone(), s))
+                    .map(|(center, slot)| async move {
+                        let first_slot = slot.start_date();
+                        let aps = center.check_appointment(&[first_slot]).await?;
+                        Ok(if aps.first().and_then(|x| x.pointer("/error")).is_some() {
+                            info!("unavailable {}", first_slot);
+                            None
+                        } else {
+                            info!("available {}", first_slot);
+                            Some(slot)
+                        })
                     })
+                    .collect::<Vec<_>>(),
+            )
+            .await;
+
+            a.into_iter().flatten().flatten().collect()
+        }
+    };
+
+    let pairs: Vec<SlotPair> = match config.second_dose {
+        SecondDose::Ignore => vec![],
+        SecondDose::Pair | SecondDose::Require => join_all(slots.iter().map(|slot| {
+            let center = &center;
+            async move {
+                let first = slot.start_date();
+                let second = center
+                    .second_slot(first, config.second_dose_delay)
+                    .await
+                    .map_err(|e| info!("no second dose for {}: {}", first, e))
                     .ok()
-                    .map(|x| x.signed_duration_since(Utc::now()).num_minutes() <= 24 * 60) // chronodose (check appointments only in 24 hours)
-                    .map(|_| x)
-            })
-            .map(|s| (center.clone(), s))
-            .map(|(center, first_slot)| async move {
-                let aps = center.check_appointment(&[first_slot]).await?;
-                Ok(if aps[0].pointer("/error").is_some() {
-                    // info!("response {}", serde_json::to_string_pretty(&aps[0]).unwrap());
-                    info!("unavailable {}", first_slot);
-                    None
-                } else {
-                    // info!(
-                    //     "response {}",
-                    //     serde_json::to_string_pretty(&aps[0]).unwrap()
-                    // );
-                    info!("available {}", first_slot);
-                    Some(first_slot.to_string())
+                    .flatten();
+                Ok::<_, anyhow::Error>(SlotPair {
+                    first: crate::util::parse_date(first)?,
+                    second: second.and_then(|x| crate::util::parse_date(&x).ok()),
                 })
-            })
-            .collect::<Vec<_>>(),
-    )
-    .await;
+            }
+        }))
+        .await
+        .into_iter()
+        .flatten()
+        .collect(),
+    };
 
-    let count = a.into_iter().flatten().flatten().count();
+    let slots: Vec<Slot> = slots
+        .into_iter()
+        .filter_map(|x| {
+            let step = x.steps().first();
+            Some(Slot {
+                start: x.start()?,
+                end: x.end_date().and_then(|x| crate::util::parse_date(x).ok()),
+                motive: step
+                    .and_then(|x| x.visit_motive_id)
+                    .and_then(|x| motive_names.get(&x).cloned())
+                    .or_else(|| single_motive.clone()),
+                agenda: step
+                    .and_then(|x| x.agenda_id)
+                    .or(single_agenda)
+                    .map(|x| x.to_string()),
+                practice: Some(practice_id.clone()),
+            })
+        })
+        .filter(|x| {
+            config.second_dose != SecondDose::Require
+                || pairs
+                    .iter()
+                    .any(|p| p.first == x.start && p.second.is_some())
+        })
+        .collect();
 
     info!(
-        "{} has {} slots on {}",
-        center_data
-            .pointer("/data/profile/name_with_title")
-            .and_then(|x| serde_json::to_string_pretty(x).ok())
-            .unwrap(),
-        count,
-        test_date
+        "{} has {} slots from {}",
+        booking
+            .profile
+            .name_with_title
+            .as_deref()
+            .unwrap_or_default(),
+        slots.len(),
+        config.range.start()
     );
 
-    Ok(count)
+    Ok(Availability {
+        slots,
+        reported_slots: None,
+        motives,
+        pairs,
+        attempts: 0,
+    })
 }
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+// https://www.doctolib.fr/booking/<center>.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookingResponse {
+    pub data: Booking,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Booking {
+    pub profile: Profile,
+    #[serde(default)]
+    pub places: Vec<Place>,
+    #[serde(default)]
+    pub visit_motives: Vec<VisitMotive>,
+    #[serde(default)]
+    pub agendas: Vec<Agenda>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: u64,
+    pub name_with_title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Place {
+    // "practice-<practice id>"
+    pub id: String,
+    #[serde(default)]
+    pub practice_ids: Vec<u64>,
+    pub name: Option<String>,
+    pub address: Option<String>,
+    pub zipcode: Option<String>,
+    pub city: Option<String>,
+    pub full_address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VisitMotive {
+    pub id: u64,
+    pub name: String,
+    pub ref_visit_motive_id: Option<u64>,
+    #[serde(default)]
+    pub first_shot_motive: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Agenda {
+    pub id: u64,
+    pub practice_id: Option<u64>,
+    #[serde(default)]
+    pub booking_disabled: bool,
+    #[serde(default)]
+    pub booking_temporary_disabled: bool,
+    // practice ids are string keys in the json
+    #[serde(default)]
+    pub visit_motive_ids_by_practice_id: HashMap<String, Vec<u64>>,
+}
+
+// https://www.doctolib.fr/availabilities.json
+// https://www.doctolib.fr/second_shot_availabilities.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AvailabilityResponse {
+    #[serde(default)]
+    pub availabilities: Vec<Day>,
+    pub total: Option<usize>,
+    pub next_slot: Option<String>,
+    pub reason: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Day {
+    pub date: String,
+    #[serde(default)]
+    pub slots: Vec<Slot>,
+}
+
+// plain start dates, or objects when the motive needs several steps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Slot {
+    Start(String),
+    Detailed {
+        start_date: String,
+        end_date: Option<String>,
+        #[serde(default)]
+        steps: Vec<Step>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Step {
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub agenda_id: Option<u64>,
+    pub practitioner_agenda_id: Option<u64>,
+    pub visit_motive_id: Option<u64>,
+}
+
+impl Slot {
+    pub fn start_date(&self) -> &str {
+        match self {
+            Slot::Start(start_date) | Slot::Detailed { start_date, .. } => start_date,
+        }
+    }
+
+    pub fn end_date(&self) -> Option<&str> {
+        match self {
+            Slot::Start(_) => None,
+            Slot::Detailed { end_date, .. } => end_date.as_deref(),
+        }
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        match self {
+            Slot::Start(_) => &[],
+            Slot::Detailed { steps, .. } => steps,
+        }
+    }
+
+    pub fn start(&self) -> Option<DateTime<FixedOffset>> {
+        crate::util::parse_date(self.start_date()).ok()
+    }
+}
+
+impl AvailabilityResponse {
+    pub fn slots(&self) -> impl Iterator<Item = &Slot> {
+        self.availabilities.iter().flat_map(|d| d.slots.iter())
+    }
+}
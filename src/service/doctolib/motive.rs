@@ -0,0 +1,211 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::model::VisitMotive;
+use crate::util::normalize_name;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Vaccine {
+    Pfizer,
+    Moderna,
+    AstraZeneca,
+    Janssen,
+}
+
+impl Vaccine {
+    pub fn all() -> [Vaccine; 4] {
+        [
+            Vaccine::Pfizer,
+            Vaccine::Moderna,
+            Vaccine::AstraZeneca,
+            Vaccine::Janssen,
+        ]
+    }
+
+    // normalized words used for the vaccine in motive names
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            Vaccine::Pfizer => &["pfizer", "biontech", "comirnaty"],
+            Vaccine::Moderna => &["moderna", "spikevax"],
+            Vaccine::AstraZeneca => &["astrazeneca", "astra", "vaxzevria"],
+            Vaccine::Janssen => &["janssen", "johnson"],
+        }
+    }
+
+    pub fn of(name: &str) -> Option<Vaccine> {
+        let name = normalize_name(name);
+        let words: Vec<_> = name.split(' ').collect();
+        Vaccine::all()
+            .iter()
+            .copied()
+            .find(|v| v.keywords().iter().any(|k| words.contains(k)))
+    }
+}
+
+impl fmt::Display for Vaccine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Vaccine::Pfizer => write!(f, "pfizer"),
+            Vaccine::Moderna => write!(f, "moderna"),
+            Vaccine::AstraZeneca => write!(f, "astrazeneca"),
+            Vaccine::Janssen => write!(f, "janssen"),
+        }
+    }
+}
+
+impl FromStr for Vaccine {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        Vaccine::of(s).ok_or_else(|| anyhow::anyhow!("unknown vaccine: {}", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dose {
+    First,
+    Second,
+    // third dose and later
+    Booster,
+}
+
+impl Dose {
+    pub fn of(motive: &VisitMotive) -> Option<Dose> {
+        let name = normalize_name(&motive.name);
+        let words: Vec<_> = name.split(' ').collect();
+        let has = |keys: &[&str]| keys.iter().any(|k| words.contains(k));
+
+        if has(&["rappel", "booster", "3e", "3eme", "3ieme", "troisieme"]) {
+            Some(Dose::Booster)
+        } else if has(&["2de", "2nde", "2e", "2eme", "deuxieme", "seconde", "second"]) {
+            Some(Dose::Second)
+        } else if motive.first_shot_motive || has(&["1re", "1ere", "1er", "premiere", "first"]) {
+            Some(Dose::First)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Dose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dose::First => write!(f, "1"),
+            Dose::Second => write!(f, "2"),
+            Dose::Booster => write!(f, "booster"),
+        }
+    }
+}
+
+impl FromStr for Dose {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match normalize_name(s).as_str() {
+            "1" | "first" | "1re" | "1ere" => Ok(Dose::First),
+            "2" | "second" | "2de" | "2nde" => Ok(Dose::Second),
+            "3" | "third" | "booster" | "rappel" => Ok(Dose::Booster),
+            _ => Err(anyhow::anyhow!("unknown dose: {}", s)),
+        }
+    }
+}
+
+// inclusive age range read from a motive name, such as
+// "18 ans et plus", "plus de 55 ans", "12-17 ans", "12 a 17 ans" or "moins de 18 ans"
+pub fn age_range(name: &str) -> Option<(u8, u8)> {
+    let name = normalize_name(name);
+    let words: Vec<_> = name.split(' ').collect();
+    let number = |i: usize| words.get(i).and_then(|x| x.parse::<u8>().ok());
+
+    words.iter().enumerate().find_map(|(i, word)| {
+        if *word != "ans" || i == 0 {
+            return None;
+        }
+        let n = number(i - 1)?;
+        let before = |k: usize| i.checked_sub(k).and_then(|j| words.get(j)).copied();
+        let after = |k: usize| words.get(i + k).copied();
+
+        if after(1) == Some("et") && after(2) == Some("plus") {
+            Some((n, u8::MAX))
+        } else if before(3) == Some("plus") && before(2) == Some("de") {
+            Some((n.saturating_add(1), u8::MAX))
+        } else if before(3) == Some("moins") && before(2) == Some("de") {
+            Some((0, n.saturating_sub(1)))
+        } else if let Some(m) = i.checked_sub(2).and_then(number) {
+            Some((m, n))
+        } else if before(2) == Some("a") {
+            i.checked_sub(3).and_then(number).map(|m| (m, n))
+        } else {
+            None
+        }
+    })
+}
+
+// empty lists accept anything
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MotiveSelector {
+    pub vaccines: Vec<Vaccine>,
+    pub doses: Vec<Dose>,
+    // age of the person to vaccinate, motives without an age range always match
+    pub age: Option<u8>,
+    // every keyword must appear in the motive name
+    pub keywords: Vec<String>,
+    // no excluded keyword may appear in the motive name
+    pub excluded: Vec<String>,
+}
+
+impl MotiveSelector {
+    // first dose of pfizer
+    pub fn pfizer_first_dose() -> Self {
+        Self::default().vaccine(Vaccine::Pfizer).dose(Dose::First)
+    }
+
+    pub fn vaccine(mut self, vaccine: Vaccine) -> Self {
+        self.vaccines.push(vaccine);
+        self
+    }
+
+    pub fn dose(mut self, dose: Dose) -> Self {
+        self.doses.push(dose);
+        self
+    }
+
+    pub fn age(mut self, age: u8) -> Self {
+        self.age = Some(age);
+        self
+    }
+
+    pub fn keyword(mut self, keyword: &str) -> Self {
+        self.keywords.push(keyword.to_owned());
+        self
+    }
+
+    pub fn exclude(mut self, keyword: &str) -> Self {
+        self.excluded.push(keyword.to_owned());
+        self
+    }
+
+    pub fn matches(&self, motive: &VisitMotive) -> bool {
+        let name = normalize_name(&motive.name);
+        let contains = |keyword: &String| name.contains(&normalize_name(keyword));
+
+        (self.vaccines.is_empty()
+            || Vaccine::of(&motive.name).is_some_and(|v| self.vaccines.contains(&v)))
+            && (self.doses.is_empty() || Dose::of(motive).is_some_and(|d| self.doses.contains(&d)))
+            && match (self.age, age_range(&motive.name)) {
+                (Some(age), Some((min, max))) => (min..=max).contains(&age),
+                _ => true,
+            }
+            && self.keywords.iter().all(contains)
+            && !self.excluded.iter().any(contains)
+    }
+
+    pub fn select<'a>(&self, motives: &'a [VisitMotive]) -> Vec<&'a VisitMotive> {
+        motives.iter().filter(|m| self.matches(m)).collect()
+    }
+}
@@ -1 +1,159 @@
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::center::Center;
+use crate::error::Error;
+use crate::http::{count_attempts, Http};
+
 pub mod doctolib;
+pub mod summary;
+
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Slot {
+    pub start: DateTime<FixedOffset>,
+    pub end: Option<DateTime<FixedOffset>>,
+    pub motive: Option<String>,
+    pub agenda: Option<String>,
+    pub practice: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Availability {
+    pub slots: Vec<Slot>,
+    // count given by providers which do not list slots
+    pub reported_slots: Option<usize>,
+    // names of the visit motives the slots were searched for
+    pub motives: Vec<String>,
+    // first dose slots with their earliest second dose, when requested
+    pub pairs: Vec<SlotPair>,
+    // requests sent to the provider, retries included, counted by ProviderRegistry
+    pub attempts: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotPair {
+    pub first: DateTime<FixedOffset>,
+    pub second: Option<DateTime<FixedOffset>>,
+}
+
+impl Availability {
+    pub fn n_slot(&self) -> usize {
+        self.reported_slots.unwrap_or(self.slots.len())
+    }
+
+    pub fn next_slot(&self) -> Option<&Slot> {
+        self.slots.iter().min_by_key(|x| x.start)
+    }
+
+    pub fn earliest_pair(&self) -> Option<&SlotPair> {
+        self.pairs
+            .iter()
+            .filter(|x| x.second.is_some())
+            .min_by_key(|x| x.first)
+    }
+}
+
+// days from start, start defaults to the local date of today
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub start: Option<NaiveDate>,
+    pub days: usize,
+}
+
+impl Default for DateRange {
+    fn default() -> Self {
+        Self {
+            start: None,
+            days: 2,
+        }
+    }
+}
+
+impl DateRange {
+    pub fn new(start: NaiveDate, days: usize) -> Self {
+        Self {
+            start: Some(start),
+            days,
+        }
+    }
+
+    pub fn start(&self) -> NaiveDate {
+        self.start.unwrap_or_else(|| Local::now().date_naive())
+    }
+
+    // exclusive
+    pub fn end(&self) -> NaiveDate {
+        self.start() + chrono::Duration::days(self.days as i64)
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        (self.start()..self.end()).contains(&date)
+    }
+}
+
+#[async_trait]
+pub trait SlotProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn detect(&self, center: &Center) -> bool;
+
+    async fn availability(&self, center: &Center) -> anyhow::Result<Availability>;
+
+    async fn slots(&self, center: &Center) -> anyhow::Result<Vec<Slot>> {
+        Ok(self.availability(center).await?.slots)
+    }
+}
+
+// providers are tried in order, the first one detecting a center is used
+pub struct ProviderRegistry {
+    providers: Vec<Box<dyn SlotProvider>>,
+}
+
+impl Default for ProviderRegistry {
+    fn default() -> Self {
+        Self::with_http(&Http::default())
+    }
+}
+
+impl ProviderRegistry {
+    pub fn empty() -> Self {
+        Self { providers: vec![] }
+    }
+
+    pub fn with_http(http: &Http) -> Self {
+        Self {
+            providers: vec![
+                Box::new(doctolib::Doctolib {
+                    http: http.clone(),
+                    ..Default::default()
+                }),
+                Box::new(summary::ViteMaDoseSummary),
+            ],
+        }
+    }
+
+    // registered providers take precedence over the existing ones
+    pub fn register<P: SlotProvider + 'static>(&mut self, provider: P) -> &mut Self {
+        self.providers.insert(0, Box::new(provider));
+        self
+    }
+
+    pub fn providers(&self) -> impl Iterator<Item = &dyn SlotProvider> {
+        self.providers.iter().map(|p| p.as_ref())
+    }
+
+    pub fn find(&self, center: &Center) -> Option<&dyn SlotProvider> {
+        self.providers().find(|p| p.detect(center))
+    }
+
+    pub async fn availability(&self, center: &Center) -> crate::Result<Availability> {
+        let provider = self
+            .find(center)
+            .ok_or_else(|| Error::NoProvider(center.url().to_owned()))?;
+        let (attempts, availability) = count_attempts(provider.availability(center)).await;
+        availability
+            .map(|x| Availability { attempts, ..x })
+            .map_err(|e| Error::provider(provider.name(), e))
+    }
+}
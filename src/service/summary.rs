@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+
+use super::{Availability, SlotProvider};
+use crate::center::Center;
+
+// uses the chronodose counts already scraped by ViteMaDose, works for every center
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ViteMaDoseSummary;
+
+#[async_trait]
+impl SlotProvider for ViteMaDoseSummary {
+    fn name(&self) -> &str {
+        "vitemadose"
+    }
+
+    fn detect(&self, _center: &Center) -> bool {
+        true
+    }
+
+    async fn availability(&self, center: &Center) -> anyhow::Result<Availability> {
+        Ok(Availability {
+            slots: vec![],
+            reported_slots: Some(center.chronodose_count()),
+            motives: vec![],
+            pairs: vec![],
+            attempts: 0,
+        })
+    }
+}
@@ -1,12 +1,59 @@
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::geo::Coordinates;
+
 pub fn lat_long_to_km(lat1: f64, long1: f64, lat2: f64, long2: f64) -> f64 {
-    // Radius of the earth: 6371 km
+    Coordinates::new(lat1, long1).distance_km(&Coordinates::new(lat2, long2))
+}
+
+// dates without offset are french summer time
+pub fn parse_date(s: &str) -> chrono::ParseResult<DateTime<FixedOffset>> {
+    DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f%:z")
+        .or_else(|_| DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f%z"))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f").map(|ndt| {
+                FixedOffset::east_opt(2 * 3600)
+                    .unwrap()
+                    .from_local_datetime(&ndt)
+                    .single()
+                    .expect("a fixed offset has no gap nor fold")
+            })
+        })
+}
 
-    let lat1 = lat1.to_radians();
-    let lat2 = lat2.to_radians();
-    let long1 = long1.to_radians();
-    let long2 = long2.to_radians();
+// jitters and other fractions, rejecting NaN and values out of [0, 1]
+pub fn fraction(x: f64) -> anyhow::Result<f64> {
+    if (0. ..=1.).contains(&x) {
+        Ok(x)
+    } else {
+        Err(anyhow::anyhow!(
+            "expected a number between 0 and 1, got {}",
+            x
+        ))
+    }
+}
 
-    let a = ((lat2 - lat1) / 2.).sin().powi(2) + ((long2 - long1) / 2.).sin().powi(2);
+pub(crate) fn deserialize_fraction<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    fraction(f64::deserialize(d)?).map_err(serde::de::Error::custom)
+}
+
+pub(crate) fn deserialize_optional_fraction<'de, D: Deserializer<'de>>(
+    d: D,
+) -> Result<Option<f64>, D::Error> {
+    Option::<f64>::deserialize(d)?
+        .map(fraction)
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
 
-    6371. * 2. * a.sqrt()
+// lowercase, strips accents and punctuation: "Côtes-d'Armor" -> "cotes d armor"
+pub(crate) fn normalize_name(name: &str) -> String {
+    deunicode::deunicode(name)
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
 }
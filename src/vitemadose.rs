@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use futures::future::join_all;
+use log::{info, warn};
+
+use crate::center::CentersInDepartment;
+use crate::commune::{CommuneIndex, CommuneResponse};
+use crate::department::DepartmentCode;
+use crate::http::{count_attempts, Http};
+
+// pub static COVIDTRACKER: &str = "https://vitemadose.covidtracker.fr/";
+pub static GITLAB: &str = "https://vitemadose.gitlab.io/vitemadose/";
+
+#[derive(Debug, Clone)]
+pub struct ViteMaDose {
+    base_url: String,
+    http: Http,
+    // searched instead of the communes endpoint
+    communes: Option<Arc<CommuneIndex>>,
+}
+
+impl Default for ViteMaDose {
+    fn default() -> Self {
+        Self::new(GITLAB)
+    }
+}
+
+impl ViteMaDose {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_http(base_url, &Http::default())
+    }
+
+    pub fn with_http(base_url: &str, http: &Http) -> Self {
+        let mut base_url = base_url.to_owned();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        Self {
+            base_url,
+            http: http.clone(),
+            communes: None,
+        }
+    }
+
+    // looks communes up in index rather than on ViteMaDose
+    pub fn with_communes(mut self, index: CommuneIndex) -> Self {
+        self.communes = Some(Arc::new(index));
+        self
+    }
+
+    pub fn http(&self) -> &Http {
+        &self.http
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn department_url(&self, code: DepartmentCode) -> String {
+        format!("{}{}.json", self.base_url, code)
+    }
+
+    pub async fn department(&self, code: DepartmentCode) -> anyhow::Result<CentersInDepartment> {
+        let url = self.department_url(code);
+        info!("Fetching {}", url);
+        let (attempts, centers) = count_attempts(async {
+            self.http
+                .send(self.http.client().get(&url))
+                .await?
+                .error_for_status()?
+                .json::<CentersInDepartment>()
+                .await
+                .map_err(anyhow::Error::from)
+        })
+        .await;
+        let mut centers = centers?;
+        centers.attempts = attempts;
+        Ok(centers)
+    }
+
+    pub async fn departments(
+        &self,
+        codes: &[DepartmentCode],
+    ) -> Vec<anyhow::Result<CentersInDepartment>> {
+        join_all(codes.iter().map(|&code| self.department(code))).await
+    }
+
+    pub async fn all(&self) -> Vec<anyhow::Result<CentersInDepartment>> {
+        // includes corsica and outre-mer
+        self.departments(&DepartmentCode::all()).await
+    }
+
+    // the query is a single percent-encoded path segment
+    pub fn communes_url(&self, query: &str) -> anyhow::Result<reqwest::Url> {
+        let mut url = reqwest::Url::parse(&self.base_url)?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("invalid base url: {}", self.base_url))?
+            .pop_if_empty()
+            .push("autocomplete-cp-insee")
+            .push(&format!("{}.json", query));
+        Ok(url)
+    }
+
+    pub async fn communes(&self, query: &str) -> anyhow::Result<CommuneResponse> {
+        let url = self.communes_url(query)?;
+        info!("Fetching {}", url);
+        Ok(self
+            .http
+            .send(self.http.client().get(url))
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    // in the communes file when given, on ViteMaDose otherwise,
+    // falling back to the bundled communes when it can't be reached
+    pub async fn search_communes(&self, query: &str) -> anyhow::Result<CommuneIndex> {
+        let matching = |index: &CommuneIndex| -> CommuneIndex {
+            index
+                .search(query)
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .into()
+        };
+        if let Some(index) = &self.communes {
+            return Ok(matching(index));
+        }
+        match self.communes(query).await {
+            Ok(response) => Ok(response.into()),
+            Err(e) => {
+                warn!("{}, searching the bundled communes", e);
+                Ok(matching(CommuneIndex::bundled()))
+            }
+        }
+    }
+}
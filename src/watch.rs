@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use rand::Rng;
+use serde::Serialize;
+
+use crate::center::CenterInfo;
+use crate::service::Slot;
+
+// interval randomly lengthened or shortened by up to jitter times itself, jitter in [0, 1]
+pub fn jittered(interval: Duration, jitter: f64) -> Duration {
+    let jitter = jitter.clamp(0., 1.);
+    let factor = 1. + jitter * rand::thread_rng().gen_range(-1.0..=1.0);
+    Duration::from_secs_f64(interval.as_secs_f64() * factor)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    // the center has slots and had none
+    Appeared,
+    // the center had slots and has none
+    Disappeared,
+    // slots were added or removed
+    Updated,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Change {
+    pub kind: ChangeKind,
+    // as last seen with slots
+    pub center: CenterInfo,
+    pub new_slots: Vec<Slot>,
+    pub gone_slots: Vec<Slot>,
+}
+
+impl Change {
+    // slots which weren't there before, to notify about
+    pub fn is_new_availability(&self) -> bool {
+        self.kind == ChangeKind::Appeared || !self.new_slots.is_empty()
+    }
+}
+
+fn missing_from(slots: &[Slot], other: &[Slot]) -> Vec<Slot> {
+    slots
+        .iter()
+        .filter(|x| !other.contains(x))
+        .cloned()
+        .collect()
+}
+
+// keeps the centers with slots of the previous run, by url
+#[derive(Debug, Default)]
+pub struct Watcher {
+    previous: HashMap<String, CenterInfo>,
+}
+
+impl Watcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn centers(&self) -> impl Iterator<Item = &CenterInfo> {
+        self.previous.values()
+    }
+
+    // changes since the previous run, every center with slots has appeared on the first one
+    // centers which failed to load this time are kept as they were, given by url
+    pub fn update(&mut self, centers: Vec<CenterInfo>, failed: &[&str]) -> Vec<Change> {
+        let mut current: HashMap<_, _> = centers
+            .into_iter()
+            .filter(|x| x.n_slot > 0)
+            .map(|x| (x.url.clone(), x))
+            .collect();
+        for url in failed {
+            if let Some(before) = self.previous.get(*url) {
+                current.insert(before.url.clone(), before.clone());
+            }
+        }
+
+        let mut changes = vec![];
+        for (url, center) in &current {
+            match self.previous.get(url) {
+                None => changes.push(Change {
+                    kind: ChangeKind::Appeared,
+                    center: center.clone(),
+                    new_slots: center.slots.clone(),
+                    gone_slots: vec![],
+                }),
+                Some(before) => {
+                    let new_slots = missing_from(&center.slots, &before.slots);
+                    let gone_slots = missing_from(&before.slots, &center.slots);
+                    // providers only reporting a count don't list slots
+                    let recount = center.slots.is_empty()
+                        && before.slots.is_empty()
+                        && center.n_slot != before.n_slot;
+                    if !new_slots.is_empty() || !gone_slots.is_empty() || recount {
+                        changes.push(Change {
+                            kind: ChangeKind::Updated,
+                            center: center.clone(),
+                            new_slots,
+                            gone_slots,
+                        });
+                    }
+                }
+            }
+        }
+        for (url, before) in &self.previous {
+            if !current.contains_key(url) {
+                changes.push(Change {
+                    kind: ChangeKind::Disappeared,
+                    center: before.clone(),
+                    new_slots: vec![],
+                    gone_slots: before.slots.clone(),
+                });
+            }
+        }
+
+        changes.sort_by(|a, b| {
+            a.center
+                .partial_cmp(&b.center)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        self.previous = current;
+        changes
+    }
+}
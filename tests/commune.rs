@@ -0,0 +1,137 @@
+use covax::commune::CommuneIndex;
+use covax::department::Department;
+use covax::http::{Http, HttpConfig};
+use covax::retry::RetryPolicy;
+use covax::vitemadose::ViteMaDose;
+
+static COMMUNES: &str = r#"[
+    {"c": "69383", "z": "69003", "n": "Lyon 3e Arrondissement", "d": "69", "g": "45.7597,4.8487"},
+    {"c": "42218", "z": "42000", "n": "Saint-Étienne", "d": "42", "g": "45.4339,4.3900"},
+    {"c": "42279", "z": "42100", "n": "Saint-Étienne", "d": "42", "g": "45.4339,4.3900"},
+    {"c": "38185", "z": "38000", "n": "Grenoble", "d": "38", "g": "45.1885,5.7245"},
+    {"c": "38169", "z": "38610", "n": "Gières", "g": "45.1814,5.7931"},
+    {"c": "2A004", "z": "20000", "n": "Ajaccio", "d": "2A", "g": "41.9192,8.7386"},
+    {"c": "97411", "z": "97400", "n": "Saint-Denis", "d": "974", "g": "-20.8823,55.4504"}
+]"#;
+
+fn index() -> CommuneIndex {
+    CommuneIndex::from_json(COMMUNES).unwrap()
+}
+
+fn names(communes: Vec<&covax::commune::Commune>) -> Vec<&str> {
+    communes.into_iter().map(|c| c.name()).collect()
+}
+
+#[test]
+fn lookups() {
+    let index = index();
+    assert_eq!(
+        names(index.by_postcode("69003")),
+        ["Lyon 3e Arrondissement"]
+    );
+    assert!(index.by_postcode("69001").is_empty());
+
+    let ajaccio = index.by_insee("2a004").unwrap();
+    assert_eq!(ajaccio.name(), "Ajaccio");
+    assert_eq!(ajaccio.department().unwrap().to_string(), "2A");
+    assert_eq!(
+        index
+            .by_insee("38169")
+            .unwrap()
+            .department()
+            .unwrap()
+            .to_string(),
+        "38"
+    );
+    assert_eq!(
+        index
+            .by_insee("97411")
+            .unwrap()
+            .department()
+            .unwrap()
+            .to_string(),
+        "974"
+    );
+
+    // exact matches before prefixes
+    assert_eq!(names(index.by_name("saint etienne")).len(), 2);
+    assert_eq!(names(index.by_name("gre")), ["Grenoble"]);
+}
+
+#[test]
+fn search_ignores_accents_and_case() {
+    let index = index();
+    assert_eq!(names(index.search("GIERES")), ["Gières"]);
+    assert_eq!(
+        names(index.search("Saint-Étienne")),
+        names(index.search("saint etienne"))
+    );
+    assert_eq!(names(index.search("42100")), ["Saint-Étienne"]);
+    assert_eq!(names(index.search("38185")), ["Grenoble"]);
+    assert_eq!(
+        index
+            .locate("lyon")
+            .unwrap()
+            .location()
+            .unwrap()
+            .to_string(),
+        "45.7597,4.8487"
+    );
+}
+
+#[tokio::test]
+async fn searches_the_communes_file_offline() {
+    // unreachable, the local index is searched instead
+    let vitemadose = ViteMaDose::new("http://127.0.0.1:9/").with_communes(index());
+    let found = vitemadose.search_communes("69003").await.unwrap();
+    assert_eq!(found.communes().len(), 1);
+    assert_eq!(found.communes()[0].insee(), "69383");
+}
+
+#[test]
+fn bundled_communes_cover_every_department() {
+    let bundled = CommuneIndex::bundled();
+    for department in Department::all() {
+        assert!(
+            bundled
+                .communes()
+                .iter()
+                .any(|c| c.department() == Some(department.code())),
+            "{}",
+            department.code()
+        );
+    }
+    assert!(bundled.communes().iter().all(|c| c.location().is_some()));
+    assert_eq!(names(bundled.search("ajaccio")), ["Ajaccio"]);
+    assert_eq!(names(bundled.search("97411")), ["Saint-Denis"]);
+}
+
+#[test]
+fn communes_queries_are_path_segments() {
+    let vitemadose = ViteMaDose::new("https://example.org/vitemadose/");
+    let url = |query| vitemadose.communes_url(query).unwrap().to_string();
+    assert_eq!(
+        url("lyon"),
+        "https://example.org/vitemadose/autocomplete-cp-insee/lyon.json"
+    );
+    assert_eq!(
+        url("../75"),
+        "https://example.org/vitemadose/autocomplete-cp-insee/..%2F75.json"
+    );
+    assert_eq!(
+        url("a?b#c d"),
+        "https://example.org/vitemadose/autocomplete-cp-insee/a%3Fb%23c%20d.json"
+    );
+}
+
+#[tokio::test]
+async fn falls_back_to_the_bundled_communes() {
+    let http = Http::new(HttpConfig {
+        retry: RetryPolicy::none(),
+        ..Default::default()
+    })
+    .unwrap();
+    let vitemadose = ViteMaDose::with_http("http://127.0.0.1:9/", &http);
+    let found = vitemadose.search_communes("grenoble").await.unwrap();
+    assert_eq!(names(found.communes().iter().collect()), ["Grenoble"]);
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use covax::config::Config;
+use covax::format::Format;
+use covax::service::doctolib::motive::Vaccine;
+
+#[test]
+fn profiles_from_toml() {
+    let config = Config::from_toml(
+        r#"
+        default_profile = "paris"
+
+        [http]
+        timeout = "20s"
+
+        [http.retry]
+        max_attempts = 2
+
+        [profiles.paris]
+        near = "75001"
+        radius_km = 20
+        vaccines = ["pfizer"]
+
+        [profiles.lyon]
+        near = "Lyon"
+        radius_km = 40
+        departments = ["69", "2A"]
+        format = "json"
+
+        [profiles.lyon.motives]
+        doses = ["first"]
+        age = 30
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.http.timeout, Some(Duration::from_secs(20)));
+    assert_eq!(config.http.retry.max_attempts, 2);
+
+    let paris = config.profile(None).unwrap();
+    assert_eq!(paris.radius_km, Some(20.));
+    assert_eq!(paris.vaccines, vec![Vaccine::Pfizer]);
+
+    let lyon = config.profile(Some("lyon")).unwrap();
+    assert_eq!(lyon.departments.len(), 2);
+    assert_eq!(lyon.format, Some(Format::Json));
+    assert_eq!(lyon.motives.unwrap().age, Some(30));
+
+    assert!(config.profile(Some("marseille")).is_err());
+    assert!(Config::from_toml("[profiles.paris]\nradius = 20").is_err());
+}
+
+#[test]
+fn jitters_are_fractions() {
+    let config =
+        Config::from_toml("[http.retry]\njitter = 0.5\n[profiles.paris]\njitter = 1.0").unwrap();
+    assert_eq!(config.http.retry.jitter, 0.5);
+    assert_eq!(config.profile(Some("paris")).unwrap().jitter, Some(1.));
+
+    for jitter in &["nan", "inf", "-0.1", "1.5"] {
+        assert!(Config::from_toml(&format!("[http.retry]\njitter = {}", jitter)).is_err());
+        assert!(Config::from_toml(&format!("[profiles.paris]\njitter = {}", jitter)).is_err());
+    }
+}
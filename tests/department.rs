@@ -0,0 +1,83 @@
+use std::collections::BTreeSet;
+
+use std::convert::TryFrom;
+
+use covax::department::{Department, DepartmentCode};
+use covax::geo::Coordinates;
+
+fn around(origin: Coordinates, radius_km: f64) -> Vec<String> {
+    Department::around(&origin, radius_km)
+        .into_iter()
+        .map(|d| d.code().to_string())
+        .collect()
+}
+
+#[test]
+fn departments_around_lyon() {
+    let codes = around(Coordinates::new(45.764, 4.8357), 60.);
+    // the ones Lyon is in the bounding box of come first
+    let first: BTreeSet<_> = codes[..4].iter().map(String::as_str).collect();
+    assert_eq!(first, ["01", "38", "42", "69"].iter().copied().collect());
+    assert_eq!(codes[0], "69");
+    // the rest only meet the edge of the circle
+    for code in &["03", "21", "39", "63", "73", "74"] {
+        assert!(!codes.iter().any(|x| x == code), "{} in {:?}", code, codes);
+    }
+}
+
+#[test]
+fn departments_around_the_louvre() {
+    let mut codes = around(Coordinates::new(48.864824, 2.334595), 20.);
+    assert_eq!(codes[0], "75");
+    codes.sort();
+    assert_eq!(codes, ["75", "77", "78", "91", "92", "93", "94", "95"]);
+}
+
+#[test]
+fn department_codes_are_checked_against_the_registry() {
+    for code in &["01", "1", "2a", "2B", "95", "971", "976", "OM"] {
+        assert!(code.parse::<DepartmentCode>().is_ok(), "{}", code);
+    }
+    for code in &["0", "20", "96", "200", "975", "977", "2C", "", "a"] {
+        assert!(code.parse::<DepartmentCode>().is_err(), "{}", code);
+    }
+    assert_eq!("2a".parse::<DepartmentCode>().unwrap().to_string(), "2A");
+    assert_eq!(DepartmentCode::try_from(1).unwrap().to_string(), "01");
+    assert!(DepartmentCode::try_from(975).is_err());
+
+    let all = DepartmentCode::all();
+    assert_eq!(all.len(), Department::all().len() + 1);
+    for code in all {
+        assert_eq!(code.to_string().parse::<DepartmentCode>().unwrap(), code);
+    }
+}
+
+#[test]
+fn department_lookups() {
+    assert_eq!(Department::by_name("ardeche").unwrap().name(), "Ardèche");
+    assert_eq!(
+        Department::by_name("COTES D'ARMOR")
+            .unwrap()
+            .code()
+            .to_string(),
+        "22"
+    );
+    assert!(Department::by_name("Paris 1er").is_none());
+
+    let idf = Department::by_region("ile de france");
+    assert_eq!(idf.len(), 8);
+    assert!(idf.iter().all(|d| d.region_name() == "Île-de-France"));
+    assert_eq!(Department::by_region_code(11).len(), 8);
+
+    let codes = |depts: Vec<&Department>| -> Vec<String> {
+        depts.iter().map(|d| d.code().to_string()).collect()
+    };
+    assert_eq!(
+        codes(Department::outre_mer()),
+        ["971", "972", "973", "974", "976"]
+    );
+    let metropole = codes(Department::metropole());
+    assert_eq!(metropole.len(), 96);
+    assert!(metropole.contains(&"2A".to_owned()) && metropole.contains(&"2B".to_owned()));
+    assert_eq!(metropole.len() + 5, Department::all().len());
+}
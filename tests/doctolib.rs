@@ -0,0 +1,315 @@
+use chrono::NaiveDate;
+use covax::service::doctolib::model::{AvailabilityResponse, BookingResponse, Slot, VisitMotive};
+use covax::service::doctolib::motive::{age_range, Dose, MotiveSelector, Vaccine};
+use covax::service::doctolib::{process_doctolib_center, Doctolib, SecondDose};
+use covax::service::DateRange;
+use serde_json::json;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[test]
+fn parse_booking_with_unknown_fields() {
+    let booking: BookingResponse = serde_json::from_str(
+        r#"{
+            "data": {
+                "profile": {"id": 1, "name_with_title": "Centre de vaccination", "speciality": null},
+                "places": [{"id": "practice-42", "practice_ids": [42], "city": "Paris", "landline_number": "01"}],
+                "visit_motives": [
+                    {"id": 7, "name": "1re injection vaccin COVID-19 (Pfizer-BioNTech)", "first_shot_motive": true},
+                    {"id": 8, "name": "2de injection vaccin COVID-19 (Pfizer-BioNTech)", "allow_new_patients": true}
+                ],
+                "agendas": [{"id": 3, "booking_disabled": false, "visit_motive_ids_by_practice_id": {"42": [7, 8]}}],
+                "specialities": []
+            }
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(booking.data.places[0].practice_ids, vec![42]);
+    assert!(booking.data.visit_motives[0].first_shot_motive);
+    assert!(!booking.data.visit_motives[1].first_shot_motive);
+    assert_eq!(
+        booking.data.agendas[0].visit_motive_ids_by_practice_id["42"],
+        vec![7, 8]
+    );
+}
+
+#[test]
+fn parse_availabilities() {
+    let response: AvailabilityResponse = serde_json::from_str(
+        r#"{
+            "availabilities": [
+                {"date": "2021-05-21", "slots": ["2021-05-21T10:00:00.000+02:00"]},
+                {"date": "2021-05-22", "slots": [{"start_date": "2021-05-22T11:00:00.000+02:00", "end_date": "2021-05-22T11:05:00.000+02:00", "steps": []}]},
+                {"date": "2021-05-23", "slots": []}
+            ],
+            "total": 2,
+            "search_result": {}
+        }"#,
+    )
+    .unwrap();
+
+    let slots: Vec<_> = response.slots().collect();
+    assert_eq!(slots.len(), 2);
+    assert!(matches!(slots[0], Slot::Start(_)));
+    assert_eq!(slots[1].end_date(), Some("2021-05-22T11:05:00.000+02:00"));
+    assert!(slots.iter().all(|x| x.start().is_some()));
+
+    let empty: AvailabilityResponse =
+        serde_json::from_str(r#"{"availabilities": [], "total": 0, "next_slot": "2021-06-01"}"#)
+            .unwrap();
+    assert_eq!(empty.next_slot.as_deref(), Some("2021-06-01"));
+}
+
+fn motive(id: u64, name: &str) -> VisitMotive {
+    VisitMotive {
+        id,
+        name: name.to_owned(),
+        ref_visit_motive_id: None,
+        first_shot_motive: false,
+    }
+}
+
+#[test]
+fn select_motives() {
+    let motives = vec![
+        motive(1, "1re injection vaccin COVID-19 (Pfizer-BioNTech)"),
+        motive(2, "2de injection vaccin COVID-19 (Pfizer-BioNTech)"),
+        motive(
+            3,
+            "Dose de rappel vaccin COVID-19 (Moderna) - 18 ans et plus",
+        ),
+        motive(4, "1re injection vaccin COVID-19 (Moderna) - 12-17 ans"),
+        motive(5, "Consultation de suivi"),
+    ];
+    let ids = |selector: MotiveSelector| -> Vec<u64> {
+        selector.select(&motives).iter().map(|m| m.id).collect()
+    };
+
+    assert_eq!(ids(MotiveSelector::pfizer_first_dose()), vec![1]);
+    assert_eq!(ids(MotiveSelector::default().dose(Dose::Booster)), vec![3]);
+    assert_eq!(
+        ids(MotiveSelector::default().vaccine(Vaccine::Moderna)),
+        vec![3, 4]
+    );
+    assert_eq!(
+        ids(MotiveSelector::default().vaccine(Vaccine::Moderna).age(30)),
+        vec![3]
+    );
+    assert_eq!(ids(MotiveSelector::default().keyword("suivi")), vec![5]);
+    assert_eq!(
+        ids(MotiveSelector::default().keyword("covid").exclude("rappel")),
+        vec![1, 2, 4]
+    );
+}
+
+#[test]
+fn parse_age_ranges() {
+    assert_eq!(age_range("18 ans et plus"), Some((18, u8::MAX)));
+    assert_eq!(
+        age_range("Personnes de plus de 55 ans"),
+        Some((56, u8::MAX))
+    );
+    assert_eq!(age_range("Moins de 18 ans"), Some((0, 17)));
+    assert_eq!(age_range("Jeunes de 12 à 17 ans"), Some((12, 17)));
+    assert_eq!(age_range("COVID-19 (Pfizer-BioNTech)"), None);
+}
+
+static CENTER: &str = "https://partners.doctolib.fr/centre-de-sante/paris/centre?pid=practice-42";
+
+fn day(date: &str, slots: &[&str]) -> serde_json::Value {
+    json!({"date": date, "slots": slots})
+}
+
+// a stand-in doctolib serving the booking of CENTER, with a single pfizer first dose motive
+async fn doctolib() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/booking/centre.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": {
+                "profile": {"id": 1, "name_with_title": "Centre"},
+                "visit_motives": [{"id": 7, "name": "1re injection vaccin COVID-19 (Pfizer-BioNTech)"}],
+                "agendas": [{"id": 3, "visit_motive_ids_by_practice_id": {"42": [7]}}]
+            }
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+async fn availabilities(server: &MockServer, start_date: &str, days: Vec<serde_json::Value>) {
+    availabilities_with(server, start_date, json!({"availabilities": days})).await;
+}
+
+async fn availabilities_with(server: &MockServer, start_date: &str, body: serde_json::Value) {
+    Mock::given(method("GET"))
+        .and(path("/availabilities.json"))
+        .and(query_param("start_date", start_date))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(server)
+        .await;
+}
+
+fn config(server: &MockServer, start: &str, days: usize) -> Doctolib {
+    Doctolib {
+        range: DateRange::new(NaiveDate::parse_from_str(start, "%Y-%m-%d").unwrap(), days),
+        base_url: server.uri(),
+        ..Default::default()
+    }
+}
+
+async fn requested(server: &MockServer, path: &str) -> Vec<String> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|x| x.url.path() == path)
+        .map(|x| x.url.to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn empty_windows_jump_to_the_next_slot() {
+    let server = doctolib().await;
+    let empty = ["2021-06-01", "2021-06-02", "2021-06-03", "2021-06-04"];
+    availabilities_with(
+        &server,
+        "2021-06-01",
+        json!({
+            "availabilities": empty.iter().map(|x| day(x, &[])).collect::<Vec<_>>(),
+            "next_slot": "2021-06-07"
+        }),
+    )
+    .await;
+    availabilities(
+        &server,
+        "2021-06-07",
+        vec![
+            day("2021-06-07", &["2021-06-07T10:00:00.000+02:00"]),
+            day("2021-06-08", &[]),
+            day("2021-06-09", &[]),
+            day("2021-06-10", &["2021-06-10T10:00:00.000+02:00"]),
+        ],
+    )
+    .await;
+
+    let availability = process_doctolib_center(CENTER, &config(&server, "2021-06-01", 8))
+        .await
+        .unwrap();
+    let starts: Vec<_> = availability
+        .slots
+        .iter()
+        .map(|x| x.start.to_rfc3339())
+        .collect();
+    // 2021-06-10 is past the range
+    assert_eq!(starts, ["2021-06-07T10:00:00+02:00"]);
+    assert_eq!(
+        availability.slots[0].motive.as_deref(),
+        Some("1re injection vaccin COVID-19 (Pfizer-BioNTech)")
+    );
+
+    let requests = requested(&server, "/availabilities.json").await;
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].contains("start_date=2021-06-07"));
+}
+
+#[tokio::test]
+async fn read_only_mode_never_claims_slots() {
+    let server = doctolib().await;
+    availabilities(
+        &server,
+        "2021-06-01",
+        vec![
+            day("2021-06-01", &["2021-06-01T10:00:00.000+02:00"]),
+            day("2021-06-02", &["2021-06-02T10:00:00.000+02:00"]),
+        ],
+    )
+    .await;
+    Mock::given(method("POST"))
+        .and(path("/appointments.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let availability = process_doctolib_center(CENTER, &config(&server, "2021-06-01", 2))
+        .await
+        .unwrap();
+    assert_eq!(availability.slots.len(), 2);
+    assert!(requested(&server, "/appointments.json").await.is_empty());
+    assert!(server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .all(|x| x.method == wiremock::http::Method::Get));
+}
+
+async fn second_shots(server: &MockServer, start_date: &str, body: serde_json::Value) {
+    Mock::given(method("GET"))
+        .and(path("/second_shot_availabilities.json"))
+        .and(query_param("start_date", start_date))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn pairs_first_dose_slots_with_second_ones() {
+    let server = doctolib().await;
+    availabilities(
+        &server,
+        "2021-06-01",
+        vec![
+            day("2021-06-01", &["2021-06-01T10:00:00.000+02:00"]),
+            day("2021-06-02", &["2021-06-02T10:00:00.000+02:00"]),
+        ],
+    )
+    .await;
+    second_shots(
+        &server,
+        "2021-06-22",
+        json!({"availabilities": [day("2021-06-22", &["2021-06-22T11:00:00.000+02:00"])]}),
+    )
+    .await;
+    // nothing 21 days after the second slot, nor at the next_slot suggested
+    second_shots(
+        &server,
+        "2021-06-23",
+        json!({"availabilities": [], "next_slot": "2021-07-05T09:00:00.000+02:00"}),
+    )
+    .await;
+    second_shots(&server, "2021-07-05", json!({"availabilities": []})).await;
+
+    let pair = Doctolib {
+        second_dose: SecondDose::Pair,
+        ..config(&server, "2021-06-01", 2)
+    };
+    let availability = process_doctolib_center(CENTER, &pair).await.unwrap();
+    assert_eq!(availability.slots.len(), 2);
+    assert_eq!(availability.pairs.len(), 2);
+    let earliest = availability.earliest_pair().unwrap();
+    assert_eq!(earliest.first.to_rfc3339(), "2021-06-01T10:00:00+02:00");
+    assert_eq!(
+        earliest.second.unwrap().to_rfc3339(),
+        "2021-06-22T11:00:00+02:00"
+    );
+    assert!(availability.pairs.iter().any(|x| x.second.is_none()));
+    let requests = requested(&server, "/second_shot_availabilities.json").await;
+    assert_eq!(requests.len(), 3);
+    assert!(requests.iter().all(|x| x.contains("first_slot=2021-06-0")));
+
+    let require = Doctolib {
+        second_dose: SecondDose::Require,
+        ..pair
+    };
+    let availability = process_doctolib_center(CENTER, &require).await.unwrap();
+    let starts: Vec<_> = availability
+        .slots
+        .iter()
+        .map(|x| x.start.to_rfc3339())
+        .collect();
+    assert_eq!(starts, ["2021-06-01T10:00:00+02:00"]);
+}
@@ -0,0 +1,87 @@
+use covax::notify::email::{Email, Security, SmtpConfig};
+use covax::notify::{Alert, Notifier};
+use covax::util::parse_date;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+// accepts a single message and returns the smtp session, client side
+async fn smtp_sink(listener: TcpListener) -> String {
+    let (socket, _) = listener.accept().await.unwrap();
+    let (reader, mut writer) = socket.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut session = String::new();
+    let mut data = false;
+
+    writer.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+    while let Some(line) = lines.next_line().await.unwrap() {
+        session.push_str(&line);
+        session.push('\n');
+        let reply: &[u8] = if data {
+            if line != "." {
+                continue;
+            }
+            data = false;
+            b"250 queued\r\n"
+        } else {
+            match line
+                .split(' ')
+                .next()
+                .unwrap()
+                .to_ascii_uppercase()
+                .as_str()
+            {
+                "EHLO" => b"250-sink\r\n250 AUTH PLAIN\r\n",
+                "AUTH" => b"235 ok\r\n",
+                "DATA" => {
+                    data = true;
+                    b"354 go ahead\r\n"
+                }
+                "QUIT" => {
+                    writer.write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                }
+                _ => b"250 ok\r\n",
+            }
+        };
+        writer.write_all(reply).await.unwrap();
+    }
+    session
+}
+
+#[tokio::test]
+async fn sends_a_digest_to_every_recipient() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let sink = tokio::spawn(smtp_sink(listener));
+
+    let config = SmtpConfig {
+        port: Some(port),
+        security: Security::Plain,
+        username: Some("user".to_owned()),
+        password: Some("secret".to_owned()),
+        ..SmtpConfig::new(
+            "127.0.0.1",
+            "covax <covax@example.org>",
+            &["a@example.org", "b@example.org"],
+        )
+    };
+    let alert = Alert {
+        name: "Centre de Paris".to_owned(),
+        address: "1 rue de Rivoli".to_owned(),
+        distance_km: 2.5,
+        url: "https://partners.doctolib.fr/centre".to_owned(),
+        vaccines: vec!["Pfizer-BioNTech".to_owned()],
+        slot_count: 1,
+        slots: vec![parse_date("2021-06-01T10:00:00+02:00").unwrap()],
+    };
+    Email::new(config).unwrap().notify(&[alert]).await.unwrap();
+
+    let session = sink.await.unwrap();
+    assert!(session.contains("AUTH PLAIN"));
+    assert!(session.contains("RCPT TO:<a@example.org>"));
+    assert!(session.contains("RCPT TO:<b@example.org>"));
+    assert!(session.contains("Subject: New vaccination slots at Centre de Paris"));
+    assert!(session.contains("Content-Type: text/plain"));
+    assert!(session.contains("Content-Type: text/html"));
+    assert!(session.contains("https://partners.doctolib.fr/centre"));
+}
@@ -0,0 +1,42 @@
+use covax::http::Http;
+use covax::Error;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[test]
+fn provider_errors_are_classified() {
+    let parse = serde_json::from_str::<u32>("nope").unwrap_err();
+    let error = Error::provider("doctolib", anyhow::Error::new(parse).context("booking"));
+    assert_eq!(error.kind(), "parse");
+
+    let rate_limited = Error::RateLimited {
+        host: "www.doctolib.fr".to_owned(),
+        retry_after: None,
+    };
+    let error = Error::provider("doctolib", rate_limited.into());
+    assert_eq!(error.kind(), "rate limited");
+
+    let error = Error::provider("doctolib", anyhow::anyhow!("no practice id in url"));
+    assert_eq!(error.kind(), "provider");
+    assert!(!error.is_filtered());
+    assert!(Error::Unlocated.is_filtered());
+}
+
+#[tokio::test]
+async fn status_errors_are_network_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(403).set_body_string("<html>Forbidden</html>"))
+        .mount(&server)
+        .await;
+
+    let http = Http::default();
+    let status = http
+        .send(http.client().get(server.uri()))
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap_err();
+    let error = Error::provider("doctolib", anyhow::Error::new(status).context("booking"));
+    assert_eq!(error.kind(), "network");
+}
@@ -0,0 +1,72 @@
+use covax::center::CenterInfo;
+use covax::format::{Format, Record};
+use covax::service::Slot;
+use covax::util::parse_date;
+
+fn center() -> CenterInfo {
+    let first = parse_date("2021-06-01T10:00:00+02:00").unwrap();
+    let second = parse_date("2021-06-29T10:00:00+02:00").unwrap();
+    CenterInfo {
+        distance: 2.5,
+        n_slot: 3,
+        next_slot: Some(first),
+        address: "1 rue de Rivoli | Paris".to_owned(),
+        url: "https://partners.doctolib.fr/centre".to_owned(),
+        pair: Some((first, second)),
+        name: "Centre, Paris".to_owned(),
+        vaccines: vec!["Pfizer-BioNTech".to_owned()],
+        slots: vec![
+            Slot {
+                start: first,
+                end: None,
+                motive: Some("1re injection vaccin COVID-19 (Pfizer-BioNTech)".to_owned()),
+                agenda: None,
+                practice: None,
+            },
+            Slot {
+                start: parse_date("2021-06-01T10:15:00+02:00").unwrap(),
+                end: None,
+                motive: None,
+                agenda: None,
+                practice: None,
+            },
+        ],
+        attempts: 1,
+    }
+}
+
+fn output(format: Format) -> String {
+    let mut out = vec![];
+    format.write(&mut out, &[center(), center()]).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn machine_readable_formats_share_a_schema() {
+    let json: Vec<Record> = serde_json::from_str(&output(Format::Json)).unwrap();
+    assert_eq!(json.len(), 2);
+    assert_eq!(json[0], Record::from(&center()));
+
+    let ndjson = output(Format::Ndjson);
+    assert_eq!(ndjson.lines().count(), 2);
+    let record: Record = serde_json::from_str(ndjson.lines().next().unwrap()).unwrap();
+    assert_eq!(record.second_dose, center().pair.map(|x| x.1));
+    assert_eq!(record.vaccines, ["Pfizer-BioNTech"]);
+    assert_eq!(record.slots.len(), 2);
+    assert_eq!(record.slots[1].motive, None);
+
+    let csv = output(Format::Csv);
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some(Record::FIELDS.join(",").as_str()));
+    let row = lines.next().unwrap();
+    assert!(row.starts_with("\"Centre, Paris\","));
+    assert!(row.ends_with(
+        ",Pfizer-BioNTech,2021-06-01T10:00:00+02:00 (1re injection vaccin COVID-19 (Pfizer-BioNTech)); 2021-06-01T10:15:00+02:00"
+    ));
+
+    let markdown = output(Format::Markdown);
+    assert_eq!(markdown.lines().count(), 4);
+    assert!(markdown.contains("Rivoli \\| Paris"));
+
+    assert_eq!("NDJSON".parse::<Format>().unwrap(), Format::Ndjson);
+}
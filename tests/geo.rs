@@ -0,0 +1,97 @@
+use covax::geo::{BoundingBox, Coordinates};
+use covax::util::lat_long_to_km;
+
+const PARIS: Coordinates = Coordinates {
+    latitude: 48.8566,
+    longitude: 2.3522,
+};
+const LYON: Coordinates = Coordinates {
+    latitude: 45.7640,
+    longitude: 4.8357,
+};
+const MARSEILLE: Coordinates = Coordinates {
+    latitude: 43.2965,
+    longitude: 5.3698,
+};
+const LONDON: Coordinates = Coordinates {
+    latitude: 51.5074,
+    longitude: -0.1278,
+};
+const NEW_YORK: Coordinates = Coordinates {
+    latitude: 40.7128,
+    longitude: -74.0060,
+};
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "expected {} +/- {}, got {}",
+        expected,
+        tolerance,
+        actual
+    );
+}
+
+#[test]
+fn known_city_distances() {
+    assert_close(PARIS.distance_km(&LYON), 391.5, 1.);
+    assert_close(PARIS.distance_km(&MARSEILLE), 660.5, 1.);
+    assert_close(PARIS.distance_km(&LONDON), 343.6, 1.);
+    assert_close(PARIS.distance_km(&NEW_YORK), 5837.2, 1.);
+}
+
+#[test]
+fn distance_is_symmetric_and_zero_on_self() {
+    assert_close(PARIS.distance_km(&PARIS), 0., 1e-9);
+    assert_close(LYON.distance_km(&PARIS), PARIS.distance_km(&LYON), 1e-9);
+}
+
+#[test]
+fn util_matches_geo() {
+    assert_close(
+        lat_long_to_km(
+            PARIS.latitude,
+            PARIS.longitude,
+            LYON.latitude,
+            LYON.longitude,
+        ),
+        PARIS.distance_km(&LYON),
+        1e-9,
+    );
+}
+
+#[test]
+fn bearings() {
+    assert_close(PARIS.bearing(&LYON), 150.5, 0.5);
+    assert_close(PARIS.bearing(&LONDON), 330.0, 0.5);
+    assert_close(PARIS.bearing(&Coordinates::new(60., 2.3522)), 0., 1e-9);
+}
+
+#[test]
+fn bounding_box_contains_circle() {
+    let bbox = LYON.bounding_box(60.);
+    assert!(bbox.contains(&LYON));
+    assert!(!bbox.contains(&PARIS));
+    assert_close(
+        LYON.distance_km(&Coordinates::new(bbox.max.latitude, LYON.longitude)),
+        60.,
+        1e-6,
+    );
+    assert_close(
+        LYON.distance_km(&Coordinates::new(LYON.latitude, bbox.min.longitude)),
+        60.,
+        0.1,
+    );
+
+    let cities = BoundingBox::from_points(&[PARIS, LYON, MARSEILLE]).unwrap();
+    assert!(cities.intersects(&bbox));
+    assert!(!cities.intersects(&NEW_YORK.bounding_box(100.)));
+}
+
+#[test]
+fn parse_coordinates() {
+    let coordinates: Coordinates = "48.864824, 2.334595".parse().unwrap();
+    assert_eq!(coordinates, Coordinates::new(48.864824, 2.334595));
+    assert!("48.86".parse::<Coordinates>().is_err());
+    assert!("98.0,2.0".parse::<Coordinates>().is_err());
+}
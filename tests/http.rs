@@ -0,0 +1,87 @@
+use std::time::{Duration, Instant};
+
+use covax::http::{count_attempts, Http, HttpConfig};
+use covax::retry::RetryPolicy;
+use covax::Error;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn http(max_attempts: u32) -> Http {
+    Http::new(HttpConfig {
+        retry: RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(10),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+async fn mount(server: &MockServer, response: ResponseTemplate, times: u64) {
+    Mock::given(method("GET"))
+        .and(path("/"))
+        .respond_with(response)
+        .up_to_n_times(times)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn retries_unavailable_responses() {
+    let server = MockServer::start().await;
+    mount(&server, ResponseTemplate::new(503), 1).await;
+    mount(&server, ResponseTemplate::new(200), 1).await;
+
+    let http = http(3);
+    let (attempts, response) = count_attempts(http.send(http.client().get(server.uri()))).await;
+    assert_eq!(response.unwrap().status(), 200);
+    assert_eq!(attempts, 2);
+}
+
+#[tokio::test]
+async fn gives_up_on_rate_limits() {
+    let server = MockServer::start().await;
+    mount(&server, ResponseTemplate::new(429), 3).await;
+
+    let http = http(2);
+    let (attempts, response) = count_attempts(http.send(http.client().get(server.uri()))).await;
+    match response.unwrap_err().downcast::<Error>().unwrap() {
+        Error::RateLimited { retry_after, .. } => assert_eq!(retry_after, None),
+        e => panic!("unexpected error {:?}", e),
+    }
+    assert_eq!(attempts, 2);
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn waits_for_retry_after() {
+    let server = MockServer::start().await;
+    mount(
+        &server,
+        ResponseTemplate::new(503).insert_header("Retry-After", "1"),
+        1,
+    )
+    .await;
+    mount(&server, ResponseTemplate::new(200), 1).await;
+
+    let http = http(2);
+    let start = Instant::now();
+    let response = http.send(http.client().get(server.uri())).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(start.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn sends_posts_once() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+
+    let http = http(3);
+    let (attempts, response) = count_attempts(http.send(http.client().post(server.uri()))).await;
+    assert_eq!(response.unwrap().status(), 503);
+    assert_eq!(attempts, 1);
+}
@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use covax::retry::RetryPolicy;
+
+#[test]
+fn backoff_grows_up_to_max() {
+    let policy = RetryPolicy {
+        initial_backoff: Duration::from_secs(1),
+        max_backoff: Duration::from_secs(5),
+        jitter: 0.,
+        ..Default::default()
+    };
+    assert_eq!(policy.backoff(1), Duration::from_secs(1));
+    assert_eq!(policy.backoff(2), Duration::from_secs(2));
+    assert_eq!(policy.backoff(3), Duration::from_secs(4));
+    assert_eq!(policy.backoff(4), Duration::from_secs(5));
+
+    let jittered = RetryPolicy {
+        jitter: 0.5,
+        ..policy
+    };
+    let backoff = jittered.backoff(2);
+    assert!(backoff >= Duration::from_secs(1) && backoff <= Duration::from_secs(3));
+}
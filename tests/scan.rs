@@ -0,0 +1,113 @@
+use async_trait::async_trait;
+use covax::center::Center;
+use covax::geo::Coordinates;
+use covax::scan::{scan, Search};
+use covax::service::doctolib::motive::Vaccine;
+use covax::service::{Availability, ProviderRegistry, SlotProvider};
+use covax::vitemadose::ViteMaDose;
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+static LYON: (f64, f64) = (45.764, 4.8357);
+
+// two slots for every center, but those whose url says they fail
+struct Stub;
+
+#[async_trait]
+impl SlotProvider for Stub {
+    fn name(&self) -> &str {
+        "stub"
+    }
+
+    fn detect(&self, _center: &Center) -> bool {
+        true
+    }
+
+    async fn availability(&self, center: &Center) -> anyhow::Result<Availability> {
+        if center.url().contains("fail") {
+            anyhow::bail!("stub failure");
+        }
+        Ok(Availability {
+            reported_slots: Some(2),
+            ..Default::default()
+        })
+    }
+}
+
+fn center(url: &str, (latitude, longitude): (f64, f64), vaccine: &str) -> serde_json::Value {
+    json!({
+        "departement": "69",
+        "nom": url,
+        "url": url,
+        "location": {"latitude": latitude, "longitude": longitude},
+        "metadata": {"address": "Lyon"},
+        "prochain_rdv": null,
+        "type": "vaccination-center",
+        "appointment_count": 2,
+        "vaccine_type": [vaccine],
+        "appointment_by_phone_only": false,
+        "last_scan_with_availabilities": null,
+        "gid": url
+    })
+}
+
+#[tokio::test]
+async fn reports_what_was_scanned() {
+    let server = MockServer::start().await;
+    let centers = json!([
+        center("https://example.org/found", LYON, "Pfizer-BioNTech"),
+        center("https://example.org/fail", LYON, "Pfizer-BioNTech"),
+        // marseille, out of range
+        center(
+            "https://example.org/far",
+            (43.2965, 5.3698),
+            "Pfizer-BioNTech"
+        ),
+        // not the vaccine searched for
+        center("https://example.org/moderna", LYON, "Moderna"),
+    ]);
+    Mock::given(method("GET"))
+        .and(path("/69.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "version": 1,
+            "last_updated": "2021-06-01T10:00:00+02:00",
+            "last_scrap": [],
+            "centres_disponibles": centers,
+            "centres_indisponibles": []
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/01.json"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let vitemadose = ViteMaDose::new(&server.uri());
+    let mut providers = ProviderRegistry::empty();
+    providers.register(Stub);
+    let mut search = Search::new(Coordinates::new(LYON.0, LYON.1), 20.);
+    search.vaccines = vec![Vaccine::Pfizer];
+    search.departments = vec!["69".parse().unwrap(), "01".parse().unwrap()];
+
+    let (found, report) = scan(&vitemadose, &providers, &search).await;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].url, "https://example.org/found");
+    assert_eq!(found[0].n_slot, 2);
+
+    assert_eq!(report.departments_fetched, vec!["69".parse().unwrap()]);
+    assert_eq!(report.departments_failed.len(), 1);
+    assert_eq!(report.departments_failed[0].source, "01");
+    assert_eq!(report.departments_failed[0].kind, "network");
+    assert_eq!(report.centers_scanned, 4);
+    assert_eq!(report.centers_found, 1);
+    // the moderna center and the one out of range
+    assert_eq!(report.centers_skipped, 2);
+    assert_eq!(report.centers_failed.len(), 1);
+    assert_eq!(report.centers_failed[0].source, "https://example.org/fail");
+    assert_eq!(report.centers_failed[0].kind, "provider");
+    assert_eq!(report.errors_by_kind["network"], 1);
+    assert_eq!(report.errors_by_kind["provider"], 1);
+    assert_eq!(report.errors_by_kind.len(), 2);
+}
@@ -0,0 +1,74 @@
+use std::time::{Duration, Instant};
+
+use covax::http::{Http, HttpConfig};
+use covax::scheduler::{RateLimit, Scheduler, SchedulerConfig};
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[test]
+fn rate_limit_matches_subdomains() {
+    let config = SchedulerConfig::default();
+    assert_eq!(
+        config.rate_limit("www.doctolib.fr"),
+        config.rate_limit("doctolib.fr")
+    );
+    assert!(config.rate_limit("vitemadose.gitlab.io").is_some());
+    assert!(config.rate_limit("notdoctolib.fr").is_none());
+}
+
+#[tokio::test]
+async fn token_bucket_spaces_requests() {
+    let mut config = SchedulerConfig::default();
+    config
+        .hosts
+        .insert("example.com".to_owned(), RateLimit::new(20., 1.));
+    let scheduler = Scheduler::new(config);
+
+    let start = Instant::now();
+    for _ in 0..5 {
+        let _permit = scheduler.acquire("example.com").await;
+    }
+    // first request is free, the other four wait 50ms each
+    assert!(start.elapsed() >= Duration::from_millis(190));
+
+    let start = Instant::now();
+    for _ in 0..5 {
+        let _permit = scheduler.acquire("unlimited.example.org").await;
+    }
+    assert!(start.elapsed() < Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn subdomains_share_their_domain_budget() {
+    let mut config = SchedulerConfig::default();
+    config
+        .hosts
+        .insert("example.com".to_owned(), RateLimit::new(20., 2.));
+    let scheduler = Scheduler::new(config);
+
+    let start = Instant::now();
+    for host in &["www.example.com", "partners.example.com", "example.com"] {
+        let _permit = scheduler.acquire(host).await;
+    }
+    // the burst of 2 is spent by the first two hosts, the third waits 50ms
+    assert!(start.elapsed() >= Duration::from_millis(40));
+}
+
+#[tokio::test]
+async fn responses_are_read_before_releasing_their_permit() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("body"))
+        .mount(&server)
+        .await;
+    let mut config = HttpConfig::default();
+    config.scheduler.concurrency = 1;
+    let http = Http::new(config).unwrap();
+
+    let response = http.send(http.client().get(server.uri())).await.unwrap();
+    // the only permit is free again although the response is still alive
+    let permit = tokio::time::timeout(Duration::from_secs(1), http.scheduler().acquire("x")).await;
+    assert!(permit.is_ok());
+    assert_eq!(response.url().as_str(), format!("{}/", server.uri()));
+    assert_eq!(response.text().await.unwrap(), "body");
+}
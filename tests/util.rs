@@ -0,0 +1,12 @@
+use covax::util::parse_date;
+
+#[test]
+fn dates_without_offset_are_french_summer_time() {
+    let date = parse_date("2021-06-01T10:00:00").unwrap();
+    assert_eq!(date.to_rfc3339(), "2021-06-01T10:00:00+02:00");
+    assert_eq!(date, parse_date("2021-06-01T08:00:00+00:00").unwrap());
+    assert_eq!(
+        parse_date("2021-06-01T10:00:00.000+0200").unwrap(),
+        parse_date("2021-06-01T10:00:00.000").unwrap()
+    );
+}
@@ -0,0 +1,64 @@
+use covax::vitemadose::ViteMaDose;
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn department(centers: serde_json::Value) -> serde_json::Value {
+    json!({
+        "version": 1,
+        "last_updated": "2021-06-01T10:00:00+02:00",
+        "last_scrap": [],
+        "centres_disponibles": centers,
+        "centres_indisponibles": []
+    })
+}
+
+fn center(url: &str) -> serde_json::Value {
+    json!({
+        "departement": "2A",
+        "nom": "Centre de vaccination d'Ajaccio",
+        "url": url,
+        "location": {"latitude": 41.9192, "longitude": 8.7386, "city": "Ajaccio", "cp": "20000"},
+        "metadata": {"address": "1 cours Napoléon"},
+        "prochain_rdv": "2021-06-02T10:00:00+02:00",
+        "plateforme": "Doctolib",
+        "type": "vaccination-center",
+        "appointment_count": 3,
+        "vaccine_type": ["Pfizer-BioNTech"],
+        "appointment_by_phone_only": false,
+        "last_scan_with_availabilities": null,
+        "gid": "d123"
+    })
+}
+
+#[tokio::test]
+async fn fetches_departments_by_code() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vitemadose/2A.json"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(department(json!([center("https://example.org/centre")]))),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/vitemadose/om.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(department(json!([]))))
+        .mount(&server)
+        .await;
+
+    let vitemadose = ViteMaDose::new(&format!("{}/vitemadose", server.uri()));
+    let corse = vitemadose.department("2a".parse().unwrap()).await.unwrap();
+    assert_eq!(corse.centres_disponibles.len(), 1);
+    assert_eq!(
+        corse.centres_disponibles[0].url(),
+        "https://example.org/centre"
+    );
+    assert_eq!(corse.attempts, 1);
+    let om = vitemadose.department("om".parse().unwrap()).await.unwrap();
+    assert!(om.centres_disponibles.is_empty());
+
+    // 404 for every other department
+    assert!(vitemadose.department("75".parse().unwrap()).await.is_err());
+}
@@ -0,0 +1,57 @@
+use covax::center::CenterInfo;
+use covax::service::Slot;
+use covax::util::parse_date;
+use covax::watch::{ChangeKind, Watcher};
+
+fn slot(start: &str) -> Slot {
+    Slot {
+        start: parse_date(start).unwrap(),
+        end: None,
+        motive: None,
+        agenda: None,
+        practice: None,
+    }
+}
+
+fn center(url: &str, slots: Vec<Slot>) -> CenterInfo {
+    CenterInfo {
+        distance: 1.,
+        n_slot: slots.len(),
+        next_slot: slots.first().map(|x| x.start),
+        address: String::new(),
+        url: url.to_owned(),
+        pair: None,
+        name: url.to_owned(),
+        vaccines: vec![],
+        slots,
+        attempts: 1,
+    }
+}
+
+#[test]
+fn reports_appeared_and_disappeared_slots() {
+    let a = slot("2021-06-01T10:00:00+02:00");
+    let b = slot("2021-06-01T10:15:00+02:00");
+    let mut watcher = Watcher::new();
+
+    let changes = watcher.update(vec![center("x", vec![a.clone()]), center("y", vec![])], &[]);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].kind, ChangeKind::Appeared);
+
+    assert!(watcher
+        .update(vec![center("x", vec![a.clone()])], &[])
+        .is_empty());
+
+    let changes = watcher.update(vec![center("x", vec![b.clone()])], &[]);
+    assert_eq!(changes[0].kind, ChangeKind::Updated);
+    assert_eq!(changes[0].new_slots, vec![b.clone()]);
+    assert_eq!(changes[0].gone_slots, vec![a]);
+    assert!(changes[0].is_new_availability());
+
+    // failed to load, kept as it was
+    assert!(watcher.update(vec![], &["x"]).is_empty());
+
+    let changes = watcher.update(vec![], &[]);
+    assert_eq!(changes[0].kind, ChangeKind::Disappeared);
+    assert!(!changes[0].is_new_availability());
+}
@@ -0,0 +1,98 @@
+use covax::http::Http;
+use covax::notify::webhook::{Webhook, WebhookConfig, WebhookKind};
+use covax::notify::{Alert, Notifier};
+use covax::util::parse_date;
+use wiremock::matchers::{header, method, path, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn alert() -> Alert {
+    Alert {
+        name: "Centre <Paris>".to_owned(),
+        address: "1 rue de Rivoli".to_owned(),
+        distance_km: 2.5,
+        url: "https://partners.doctolib.fr/centre".to_owned(),
+        vaccines: vec!["Pfizer-BioNTech".to_owned()],
+        slot_count: 1,
+        slots: vec![parse_date("2021-06-01T10:00:00+02:00").unwrap()],
+    }
+}
+
+async fn posted(server: &MockServer) -> serde_json::Value {
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    serde_json::from_slice(&requests[0].body).unwrap()
+}
+
+#[tokio::test]
+async fn posts_payloads_to_webhooks() {
+    let http = Http::default();
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    let url = format!("{}/hook", server.uri());
+    Webhook::new(url.parse().unwrap(), &http)
+        .notify(&[alert()])
+        .await
+        .unwrap();
+    let payload = posted(&server).await;
+    assert_eq!(payload["event"], "new_availability");
+    assert_eq!(payload["centers"][0]["url"], alert().url);
+    assert_eq!(
+        payload["centers"][0]["slots"][0],
+        "2021-06-01T10:00:00+02:00"
+    );
+
+    server.reset().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    let slack: WebhookConfig = format!("slack={}/hook", server.uri()).parse().unwrap();
+    assert_eq!(slack.kind, WebhookKind::Slack);
+    assert!("matrix=https://matrix.org"
+        .parse::<WebhookConfig>()
+        .is_err());
+    Webhook::new(slack, &http).notify(&[alert()]).await.unwrap();
+    let payload = posted(&server).await;
+    let text = payload["blocks"][0]["text"]["text"].as_str().unwrap();
+    assert!(text.starts_with("*<https://partners.doctolib.fr/centre|Centre &lt;Paris&gt;>*"));
+
+    server.reset().await;
+    Mock::given(method("PUT"))
+        .and(path_regex(
+            r"^/_matrix/client/r0/rooms/!room:example\.org/send/m\.room\.message/.+$",
+        ))
+        .and(header("authorization", "Bearer token"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    let matrix = WebhookConfig {
+        room: Some("!room:example.org".to_owned()),
+        access_token: Some("token".to_owned()),
+        ..WebhookConfig::new(WebhookKind::Matrix, &server.uri())
+    };
+    Webhook::new(matrix, &http)
+        .notify(&[alert()])
+        .await
+        .unwrap();
+    let payload = posted(&server).await;
+    assert_eq!(payload["msgtype"], "m.notice");
+}
+
+#[tokio::test]
+async fn reports_failed_deliveries() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    let webhook = Webhook::new(
+        WebhookConfig::new(WebhookKind::Discord, &server.uri()),
+        &Http::default(),
+    );
+    assert!(webhook.notify(&[alert()]).await.is_err());
+}